@@ -0,0 +1,68 @@
+use alloy_primitives::{Address, Bytes};
+use anyhow::{bail, Result};
+use revm::db::{CacheDB, EmptyDB};
+use revm::primitives::{ExecutionResult, Output, SpecId, TxKind};
+use revm::Evm;
+
+/// An in-process EVM in which contracts can be deployed and called,
+/// reporting the gas used by each transaction.
+pub struct GasMeter {
+    evm: Evm<'static, (), CacheDB<EmptyDB>>,
+}
+
+impl Default for GasMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GasMeter {
+    pub fn new() -> Self {
+        let evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .with_spec_id(SpecId::CANCUN)
+            .build();
+        Self { evm }
+    }
+
+    /// Deploy a contract from its creation code (including any encoded
+    /// constructor arguments), returning its address.
+    pub fn deploy(&mut self, creation_code: &[u8]) -> Result<Address> {
+        match self.transact(TxKind::Create, creation_code)? {
+            ExecutionResult::Success {
+                output: Output::Create(_, Some(address)),
+                ..
+            } => Ok(address),
+            result => bail!("deployment failed: {result:?}"),
+        }
+    }
+
+    /// Call the contract at `address`, returning the total gas used by the
+    /// transaction (including the 21000 base cost and the calldata cost).
+    pub fn call(&mut self, address: Address, calldata: &[u8]) -> Result<u64> {
+        Ok(self.call_with_output(address, calldata)?.0)
+    }
+
+    /// Call the contract at `address`, returning the total gas used by the
+    /// transaction and the data it returned.
+    pub fn call_with_output(&mut self, address: Address, calldata: &[u8]) -> Result<(u64, Bytes)> {
+        match self.transact(TxKind::Call(address), calldata)? {
+            ExecutionResult::Success {
+                gas_used,
+                output: Output::Call(output),
+                ..
+            } => Ok((gas_used, output)),
+            result => bail!("call failed: {result:?}"),
+        }
+    }
+
+    fn transact(&mut self, kind: TxKind, data: &[u8]) -> Result<ExecutionResult> {
+        let tx = self.evm.tx_mut();
+        tx.transact_to = kind;
+        tx.data = Bytes::copy_from_slice(data);
+        tx.gas_limit = 30_000_000;
+        self.evm
+            .transact_commit()
+            .map_err(|e| anyhow::anyhow!("transaction failed: {e:?}"))
+    }
+}
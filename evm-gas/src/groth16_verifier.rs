@@ -0,0 +1,366 @@
+//! An EVM Groth16 verifier for a fixed verifying key.
+//!
+//! The generated contract performs the same checks as the usual Solidity
+//! verifiers (e.g. those exported by snarkjs), using the BN254 precompiles:
+//! `ecMul` and `ecAdd` to compute the input commitment, then a single
+//! `ecPairing` call. It is emitted directly as bytecode so that no Solidity
+//! compiler is needed. It accepts calldata for
+//!
+//!   verifyProof(uint256[2] a, uint256[2][2] b, uint256[2] c, uint256[N] input)
+//!
+//! and returns `1` for a valid proof, reverting otherwise (including for any
+//! other function selector).
+//!
+//! Being hand-assembled, it has none of the ABI decoding, memory management
+//! and dispatch overhead of a compiled Solidity verifier, so the gas it uses
+//! is a lower bound on the cost of direct verification. Most of the gas is
+//! taken by the precompile calls (see `precompile_gas`), which any verifier
+//! makes.
+
+use alloy_primitives::keccak256;
+use ark_bn254::{Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use groth16::{Inputs, Proof, VerifyingKey};
+
+// Opcodes
+const SUB: u8 = 0x03;
+const MOD: u8 = 0x06;
+const LT: u8 = 0x10;
+const EQ: u8 = 0x14;
+const AND: u8 = 0x16;
+const SHR: u8 = 0x1c;
+const CALLDATALOAD: u8 = 0x35;
+const CALLDATASIZE: u8 = 0x36;
+const CODECOPY: u8 = 0x39;
+const MLOAD: u8 = 0x51;
+const MSTORE: u8 = 0x52;
+const JUMPI: u8 = 0x57;
+const GAS: u8 = 0x5a;
+const JUMPDEST: u8 = 0x5b;
+const PUSH1: u8 = 0x60;
+const PUSH2: u8 = 0x61;
+const DUP1: u8 = 0x80;
+const SWAP1: u8 = 0x90;
+const STATICCALL: u8 = 0xfa;
+const RETURN: u8 = 0xf3;
+const REVERT: u8 = 0xfd;
+
+// Precompiles
+const EC_ADD: u8 = 0x06;
+const EC_MUL: u8 = 0x07;
+const EC_PAIRING: u8 = 0x08;
+
+// Memory layout: the input commitment is accumulated at 0x00, with the
+// `ecMul` operands at 0x40 (so that the product lands directly after it, as
+// the second `ecAdd` operand). The `ecPairing` input starts at 0x100.
+const ACC: u16 = 0x00;
+const MUL_IN: u16 = 0x40;
+const SCALAR: u16 = 0x80;
+const PAIRING_IN: u16 = 0x100;
+const PAIRING_IN_LEN: u16 = 0x300;
+
+// Precompile gas costs, from EIP-1108
+const EC_ADD_GAS: u64 = 150;
+const EC_MUL_GAS: u64 = 6000;
+const EC_PAIRING_BASE_GAS: u64 = 45000;
+const EC_PAIRING_PER_PAIR_GAS: u64 = 34000;
+
+/// Gas used by the precompile calls of the verifier for `num_inputs` public
+/// inputs: an `ecMul` and `ecAdd` per input, and a 4-pair `ecPairing`.
+pub fn precompile_gas(num_inputs: usize) -> u64 {
+    num_inputs as u64 * (EC_MUL_GAS + EC_ADD_GAS)
+        + EC_PAIRING_BASE_GAS
+        + 4 * EC_PAIRING_PER_PAIR_GAS
+}
+
+/// Offset of the calldata word `i` (after the selector).
+fn calldata_word(i: usize) -> u16 {
+    (4 + 32 * i) as u16
+}
+
+/// Big-endian bytes of a field element
+fn fe_bytes<F: PrimeField>(f: &F) -> [u8; 32] {
+    f.into_bigint()
+        .to_bytes_be()
+        .try_into()
+        .expect("32 byte field element")
+}
+
+/// EVM encoding of a G1 point: (x, y), with (0, 0) for the identity.
+pub fn g1_words(p: &G1Affine) -> [[u8; 32]; 2] {
+    if p.infinity {
+        return [[0u8; 32]; 2];
+    }
+    [fe_bytes(&p.x), fe_bytes(&p.y)]
+}
+
+/// EVM encoding of a G2 point: (x.c1, x.c0, y.c1, y.c0), with zeros for the
+/// identity. Note that EIP-197 puts the imaginary part first.
+pub fn g2_words(p: &G2Affine) -> [[u8; 32]; 4] {
+    if p.infinity {
+        return [[0u8; 32]; 4];
+    }
+    let fq2 = |f: &Fq2| [fe_bytes(&f.c1), fe_bytes(&f.c0)];
+    let [x1, x0] = fq2(&p.x);
+    let [y1, y0] = fq2(&p.y);
+    [x1, x0, y1, y0]
+}
+
+/// Function selector of `verifyProof` for `num_inputs` public inputs.
+pub fn selector(num_inputs: usize) -> [u8; 4] {
+    let signature =
+        format!("verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{num_inputs}])");
+    keccak256(signature.as_bytes())[..4].try_into().unwrap()
+}
+
+/// Calldata for verifying `proof` against `inputs`.
+pub fn verify_calldata(proof: &Proof, inputs: &Inputs) -> Vec<u8> {
+    let mut calldata = selector(inputs.len()).to_vec();
+    let words = g1_words(&proof.pi_a)
+        .into_iter()
+        .chain(g2_words(&proof.pi_b))
+        .chain(g1_words(&proof.pi_c))
+        .chain(inputs.iter().map(fe_bytes));
+    for word in words {
+        calldata.extend_from_slice(&word);
+    }
+    calldata
+}
+
+/// A minimal bytecode assembler.
+#[derive(Default)]
+struct Assembler {
+    code: Vec<u8>,
+}
+
+impl Assembler {
+    fn op(&mut self, op: u8) -> &mut Self {
+        self.code.push(op);
+        self
+    }
+
+    /// Push a big-endian value, using the shortest PUSH instruction.
+    fn push(&mut self, value: &[u8]) -> &mut Self {
+        let first_nonzero = value.iter().position(|b| *b != 0);
+        let value = match first_nonzero {
+            Some(i) => &value[i..],
+            None => &[0u8],
+        };
+        assert!(value.len() <= 32);
+        self.code.push(PUSH1 + value.len() as u8 - 1);
+        self.code.extend_from_slice(value);
+        self
+    }
+
+    fn push_u16(&mut self, value: u16) -> &mut Self {
+        self.push(&value.to_be_bytes())
+    }
+
+    /// Push a 2 byte value with a fixed-length instruction, for jump targets
+    /// and code offsets computed before the code is complete.
+    fn push2(&mut self, value: u16) -> &mut Self {
+        self.code.push(PUSH2);
+        self.code.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// mem[offset] = value
+    fn mstore(&mut self, offset: u16, value: &[u8]) -> &mut Self {
+        self.push(value).push_u16(offset).op(MSTORE)
+    }
+
+    /// mem[offset] = calldata[calldata_offset]
+    fn mstore_calldata(&mut self, offset: u16, calldata_offset: u16) -> &mut Self {
+        self.push_u16(calldata_offset)
+            .op(CALLDATALOAD)
+            .push_u16(offset)
+            .op(MSTORE)
+    }
+
+    /// staticcall(gas(), address, input, input_len, output, output_len),
+    /// ANDing the success flag into the value on top of the stack.
+    fn staticcall_and(
+        &mut self,
+        address: u8,
+        input: u16,
+        input_len: u16,
+        output: u16,
+        output_len: u16,
+    ) -> &mut Self {
+        self.push_u16(output_len)
+            .push_u16(output)
+            .push_u16(input_len)
+            .push_u16(input)
+            .push(&[address])
+            .op(GAS)
+            .op(STATICCALL)
+            .op(AND)
+    }
+}
+
+/// Runtime bytecode of the verifier for `vk`.
+pub fn verifier_runtime_code(vk: &VerifyingKey) -> Vec<u8> {
+    let num_inputs = vk.s.len() - 1;
+    let q = Fq::MODULUS.to_bytes_be();
+    let r = Fr::MODULUS.to_bytes_be();
+    let mut asm = Assembler::default();
+
+    // The stack holds a single flag, which is cleared by any failed check.
+    asm.push(&[1]);
+
+    // Check the function selector and the calldata length
+    asm.push(&[0])
+        .op(CALLDATALOAD)
+        .push(&[0xe0])
+        .op(SHR)
+        .push(&selector(num_inputs))
+        .op(EQ)
+        .op(AND);
+    let calldata_len = calldata_word(8 + num_inputs);
+    asm.op(CALLDATASIZE).push_u16(calldata_len).op(EQ).op(AND);
+
+    // acc = s[0] + \sum_i input[i] * s[i + 1]
+    let [x, y] = g1_words(&vk.s[0]);
+    asm.mstore(ACC, &x).mstore(ACC + 0x20, &y);
+    for (i, s) in vk.s[1..].iter().enumerate() {
+        let [x, y] = g1_words(s);
+        asm.mstore(MUL_IN, &x).mstore(MUL_IN + 0x20, &y);
+        // Check input < r and store it as the scalar
+        asm.push_u16(calldata_word(8 + i))
+            .op(CALLDATALOAD)
+            .op(DUP1)
+            .push(&r)
+            .op(SWAP1)
+            .op(LT)
+            .op(SWAP1)
+            .push_u16(SCALAR)
+            .op(MSTORE)
+            .op(AND);
+        asm.staticcall_and(EC_MUL, MUL_IN, 0x60, MUL_IN, 0x40);
+        asm.staticcall_and(EC_ADD, ACC, 0x80, ACC, 0x40);
+    }
+
+    // e(-a, b) e(alpha, beta) e(acc, gamma) e(c, delta) == 1
+    let mut offset = PAIRING_IN;
+    let mut next = || {
+        let o = offset;
+        offset += 0x20;
+        o
+    };
+    // -a = (a.x, (q - a.y % q) % q)
+    asm.mstore_calldata(next(), calldata_word(0));
+    asm.push(&q)
+        .push_u16(calldata_word(1))
+        .op(CALLDATALOAD)
+        .op(MOD)
+        .push(&q)
+        .op(SUB)
+        .push(&q)
+        .op(SWAP1)
+        .op(MOD)
+        .push_u16(next())
+        .op(MSTORE);
+    for i in 2..6 {
+        asm.mstore_calldata(next(), calldata_word(i));
+    }
+    for word in g1_words(&vk.alpha).iter().chain(&g2_words(&vk.beta)) {
+        asm.mstore(next(), word);
+    }
+    for word_offset in [ACC, ACC + 0x20] {
+        asm.push_u16(word_offset)
+            .op(MLOAD)
+            .push_u16(next())
+            .op(MSTORE);
+    }
+    for word in g2_words(&vk.gamma) {
+        asm.mstore(next(), &word);
+    }
+    for i in 6..8 {
+        asm.mstore_calldata(next(), calldata_word(i));
+    }
+    for word in g2_words(&vk.delta) {
+        asm.mstore(next(), &word);
+    }
+    assert_eq!(offset, PAIRING_IN + PAIRING_IN_LEN);
+    asm.staticcall_and(EC_PAIRING, PAIRING_IN, PAIRING_IN_LEN, 0x00, 0x20);
+    asm.push(&[0x00]).op(MLOAD).op(AND);
+
+    // Return 1 if all checks passed, otherwise revert.
+    let success = asm.code.len() + 4 + 4;
+    asm.push2(success as u16).op(JUMPI);
+    asm.push(&[0]).op(DUP1).op(REVERT);
+    assert_eq!(asm.code.len(), success);
+    asm.op(JUMPDEST)
+        .mstore(0x00, &[1])
+        .push(&[0x20])
+        .push(&[0x00])
+        .op(RETURN);
+    asm.code
+}
+
+/// Creation bytecode, which deploys the verifier for `vk`.
+pub fn verifier_creation_code(vk: &VerifyingKey) -> Vec<u8> {
+    let runtime = verifier_runtime_code(vk);
+    // Copy the runtime code, which follows this prefix, to memory and return
+    // it.
+    const PREFIX_LEN: u16 = 13;
+    let mut asm = Assembler::default();
+    asm.push2(runtime.len() as u16)
+        .op(DUP1)
+        .push2(PREFIX_LEN)
+        .push(&[0])
+        .op(CODECOPY)
+        .push(&[0])
+        .op(RETURN);
+    assert_eq!(asm.code.len(), PREFIX_LEN as usize);
+    asm.code.extend_from_slice(&runtime);
+    asm.code
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::evm::GasMeter;
+    use ark_ff::One;
+    use groth16::sample::{sample, Sample};
+
+    #[test]
+    fn test_groth16_verifier() {
+        let Sample {
+            vk,
+            proof,
+            mut inputs,
+        } = sample();
+
+        let mut evm = GasMeter::new();
+        let verifier = evm.deploy(&verifier_creation_code(&vk)).unwrap();
+        let gas = evm
+            .call(verifier, &verify_calldata(&proof, &inputs))
+            .unwrap();
+        assert!(gas > 21000 + precompile_gas(inputs.len()));
+
+        // Another function selector
+        let mut calldata = verify_calldata(&proof, &inputs);
+        calldata[..4].copy_from_slice(&selector(inputs.len() + 1));
+        assert!(evm.call(verifier, &calldata).is_err());
+        calldata[..4].copy_from_slice(&[0; 4]);
+        assert!(evm.call(verifier, &calldata).is_err());
+
+        // Missing input
+        let mut calldata = verify_calldata(&proof, &inputs);
+        calldata.truncate(calldata.len() - 32);
+        assert!(evm.call(verifier, &calldata).is_err());
+
+        // Input not in the scalar field
+        let mut calldata = verify_calldata(&proof, &inputs);
+        let last = calldata.len() - 32;
+        calldata[last..].copy_from_slice(&Fr::MODULUS.to_bytes_be());
+        assert!(evm.call(verifier, &calldata).is_err());
+
+        // Failure case
+        inputs[0] = Fr::one();
+        assert!(evm
+            .call(verifier, &verify_calldata(&proof, &inputs))
+            .is_err());
+    }
+}
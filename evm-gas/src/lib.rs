@@ -0,0 +1,5 @@
+//! Gas measurements for on-chain verification of Groth16 application proofs
+//! and zkVM aggregation proofs, using an in-process EVM.
+
+pub mod evm;
+pub mod groth16_verifier;
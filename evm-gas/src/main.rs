@@ -0,0 +1,147 @@
+//! Measure the gas cost of verifying the application proof directly, and of
+//! verifying saved zkVM aggregation proofs.
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use evm_gas::evm::GasMeter;
+use evm_gas::groth16_verifier::{precompile_gas, verifier_creation_code, verify_calldata};
+use groth16::has_json_repr::try_load_json;
+use groth16::sample::{SAMPLE_INPUTS_FILE, SAMPLE_PROOF_FILE, SAMPLE_VK_FILE};
+use groth16::{Inputs, Proof, VerifyingKey};
+use std::path::{Path, PathBuf};
+
+/// The arguments for the gas command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct GasArgs {
+    /// Verification key JSON file of the application proof
+    #[clap(long, default_value = SAMPLE_VK_FILE)]
+    vk: String,
+
+    /// Application proof JSON file, verified directly
+    #[clap(long, default_value = SAMPLE_PROOF_FILE)]
+    proof: String,
+
+    /// Public inputs JSON file of the application proof
+    #[clap(long, default_value = SAMPLE_INPUTS_FILE)]
+    inputs: String,
+
+    /// Creation code (hex) of a Groth16 verifier contract for the application
+    /// verification key compiled with solc, e.g. from the `verifier.sol`
+    /// exported by snarkjs, to measure alongside the hand-assembled verifier
+    #[clap(long)]
+    direct_verifier: Option<PathBuf>,
+
+    /// Creation code (hex, including encoded constructor arguments) of the
+    /// RISC Zero Groth16 verifier contract
+    #[clap(long, requires = "risc0_calldata")]
+    risc0_verifier: Option<PathBuf>,
+
+    /// Calldata JSON written by the RISC Zero `export` command
+    #[clap(long, requires = "risc0_verifier")]
+    risc0_calldata: Option<PathBuf>,
+
+    /// Creation code (hex, including encoded constructor arguments) of the
+    /// SP1 verifier contract
+    #[clap(long, requires = "sp1_calldata")]
+    sp1_verifier: Option<PathBuf>,
+
+    /// Calldata JSON written by the SP1 `export` command
+    #[clap(long, requires = "sp1_verifier")]
+    sp1_calldata: Option<PathBuf>,
+}
+
+/// Read a hex file (optionally 0x-prefixed).
+fn read_hex(path: &Path) -> Result<Vec<u8>> {
+    let s = std::fs::read_to_string(path).with_context(|| format!("{}", path.display()))?;
+    let s = s.trim();
+    hex::decode(s.strip_prefix("0x").unwrap_or(s)).with_context(|| format!("{}", path.display()))
+}
+
+/// Read the calldata and the batch size (the first committed value) from an
+/// exported calldata JSON file. The batch size must be non-zero.
+fn read_calldata(path: &Path, public_values_field: &str) -> Result<(Vec<u8>, u32)> {
+    let json: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(path)?).context("calldata json")?;
+    let field = |name: &str| -> Result<Vec<u8>> {
+        let s = json[name]
+            .as_str()
+            .ok_or_else(|| anyhow!("missing field {name}"))?;
+        Ok(hex::decode(s.trim_start_matches("0x"))?)
+    };
+    let public_values = field(public_values_field)?;
+    let batch_size = u32::from_le_bytes(
+        public_values
+            .get(..4)
+            .ok_or_else(|| anyhow!("no committed batch size"))?
+            .try_into()?,
+    );
+    if batch_size == 0 {
+        return Err(anyhow!("committed batch size is 0"));
+    }
+    Ok((field("calldata")?, batch_size))
+}
+
+/// Deploy an aggregation proof verifier and report the gas used to verify a
+/// proof.
+fn measure_aggregation(
+    evm: &mut GasMeter,
+    name: &str,
+    verifier: &Path,
+    calldata: &Path,
+    public_values_field: &str,
+) -> Result<()> {
+    let address = evm.deploy(&read_hex(verifier)?)?;
+    let (calldata, batch_size) = read_calldata(calldata, public_values_field)?;
+    let gas = evm.call(address, &calldata)?;
+    println!("{name} aggregation proof verification gas: {gas}");
+    println!("{name} batch size: {batch_size}");
+    println!(
+        "{name} amortised gas per application proof: {}",
+        gas / batch_size as u64
+    );
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = GasArgs::parse();
+
+    // Read input from JSON
+    let inputs: Inputs = try_load_json(&args.inputs)?;
+    let proof: Proof = try_load_json(&args.proof)?;
+    let groth16_vk: VerifyingKey = try_load_json(&args.vk)?;
+    println!("Public input length: {}", inputs.len());
+
+    let mut evm = GasMeter::new();
+
+    // Direct verification of the application proof. The hand-assembled
+    // verifier gives a lower bound, and a compiled verifier, if given, the
+    // cost of a typical deployment.
+    let calldata = verify_calldata(&proof, &inputs);
+    let verifier = evm.deploy(&verifier_creation_code(&groth16_vk))?;
+    let gas = evm.call(verifier, &calldata)?;
+    println!("Direct verification gas per application proof (lower bound): {gas}");
+    println!(
+        "Direct verification precompile gas: {}",
+        precompile_gas(inputs.len())
+    );
+    if let Some(path) = &args.direct_verifier {
+        let verifier = evm.deploy(&read_hex(path)?)?;
+        let (gas, output) = evm.call_with_output(verifier, &calldata)?;
+        // `verifyProof` returns a bool, rather than reverting
+        if output.last() != Some(&1) {
+            return Err(anyhow!(
+                "{} did not accept the proof; was it generated for --vk?",
+                path.display()
+            ));
+        }
+        println!("Direct verification gas per application proof (compiled verifier): {gas}");
+    }
+
+    if let (Some(verifier), Some(calldata)) = (&args.risc0_verifier, &args.risc0_calldata) {
+        measure_aggregation(&mut evm, "RiscZero", verifier, calldata, "journal")?;
+    }
+    if let (Some(verifier), Some(calldata)) = (&args.sp1_verifier, &args.sp1_calldata) {
+        measure_aggregation(&mut evm, "SP1", verifier, calldata, "publicValues")?;
+    }
+    Ok(())
+}
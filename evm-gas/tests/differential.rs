@@ -0,0 +1,235 @@
+//! Differential tests of the BN254 arithmetic used by `groth16::verify`
+//! against the Ethereum `ecPairing` precompile (EIP-197), as implemented by
+//! revm, and of `groth16::verify_checked` against the on-chain verifier.
+
+use alloy_primitives::keccak256;
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use evm_gas::evm::GasMeter;
+use evm_gas::groth16_verifier::{g1_words, g2_words, verifier_creation_code, verify_calldata};
+use groth16::error::VerifyError;
+use groth16::sample::{sample, Sample};
+use groth16::validate::{fp_from_bigint, g1_from_xy, g2_from_xy};
+use groth16::{verify_checked, Inputs, Proof};
+use revm::precompile::bn128::pair::{ISTANBUL_PAIR_BASE, ISTANBUL_PAIR_PER_POINT};
+use revm::precompile::bn128::run_pair;
+use serde::Deserialize;
+
+/// Size of each (G1, G2) pair in the precompile input
+const PAIR_LEN: usize = 192;
+
+/// The `ecPairing` precompile as implemented by revm: `Some(result)`, or
+/// `None` if the call fails.
+fn precompile(input: &[u8]) -> Option<bool> {
+    let output = run_pair(input, ISTANBUL_PAIR_PER_POINT, ISTANBUL_PAIR_BASE, u64::MAX).ok()?;
+    Some(output.bytes[31] == 1)
+}
+
+/// The same check using `groth16`'s point validation and the arkworks
+/// pairing: `None` if the input is malformed.
+fn pairing_check(input: &[u8]) -> Option<bool> {
+    if !input.len().is_multiple_of(PAIR_LEN) {
+        return None;
+    }
+    let fq = |bytes: &[u8]| -> Result<Fq, VerifyError> {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 32 - 8 * (i + 1);
+            *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap());
+        }
+        fp_from_bigint(ark_ff::BigInt(limbs))
+    };
+    let mut g1s = Vec::new();
+    let mut g2s = Vec::new();
+    for pair in input.chunks(PAIR_LEN) {
+        let words: Vec<Fq> = pair.chunks(32).map(fq).collect::<Result<_, _>>().ok()?;
+        // G2 coordinates have the imaginary part first
+        let (x, y) = (words[0], words[1]);
+        let (bx, by) = (Fq2::new(words[3], words[2]), Fq2::new(words[5], words[4]));
+        let a = if x.is_zero() && y.is_zero() {
+            G1Affine::identity()
+        } else {
+            g1_from_xy(x, y).ok()?
+        };
+        let b = if bx.is_zero() && by.is_zero() {
+            G2Affine::identity()
+        } else {
+            g2_from_xy(bx, by).ok()?
+        };
+        g1s.push(a);
+        g2s.push(b);
+    }
+    Some(Bn254::multi_pairing(g1s, g2s).0.is_one())
+}
+
+fn encode(pairs: &[(G1Affine, G2Affine)]) -> Vec<u8> {
+    let mut input = Vec::new();
+    for (a, b) in pairs {
+        for word in g1_words(a).iter().chain(&g2_words(b)) {
+            input.extend_from_slice(word);
+        }
+    }
+    input
+}
+
+/// Deterministic pseudo-random scalars
+fn scalar(seed: &str, i: u64) -> Fr {
+    Fr::from_be_bytes_mod_order(keccak256(format!("{seed}{i}")).as_slice())
+}
+
+fn g1(k: Fr) -> G1Affine {
+    (G1Affine::generator() * k).into_affine()
+}
+
+fn g2(k: Fr) -> G2Affine {
+    (G2Affine::generator() * k).into_affine()
+}
+
+/// A point on the twist which is not in G2
+fn g2_outside_subgroup() -> G2Affine {
+    (1u64..)
+        .filter_map(|i| G2Affine::get_point_from_x_unchecked(Fq2::new(Fq::from(i), Fq::ONE), false))
+        .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+        .unwrap()
+}
+
+fn check(name: &str, input: &[u8], expected: Option<bool>) {
+    assert_eq!(precompile(input), expected, "{name}: precompile");
+    assert_eq!(pairing_check(input), expected, "{name}: groth16");
+}
+
+/// A test vector in the go-ethereum precompile test format
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TestVector {
+    name: String,
+    input: String,
+    expected: String,
+    gas: u64,
+}
+
+/// The `bn256Pairing` (EIP-197) test vectors from go-ethereum
+/// (`core/vm/testdata/precompiles/bn256Pairing.json`), which all have valid
+/// inputs. The precompile must also charge the gas given for each.
+#[test]
+fn test_eip197_vectors() {
+    let file = std::fs::read_to_string("tests/data/bn256Pairing.json").unwrap();
+    let vectors: Vec<TestVector> = serde_json::from_str(&file).unwrap();
+    assert_eq!(vectors.len(), 14);
+    for vector in &vectors {
+        let input = hex::decode(&vector.input).unwrap();
+        let expected = hex::decode(&vector.expected).unwrap();
+        let output = run_pair(
+            &input,
+            ISTANBUL_PAIR_PER_POINT,
+            ISTANBUL_PAIR_BASE,
+            u64::MAX,
+        )
+        .unwrap_or_else(|e| panic!("{}: {e:?}", vector.name));
+        assert_eq!(output.bytes.as_ref(), expected, "{}", vector.name);
+        assert_eq!(output.gas_used, vector.gas, "{}: gas", vector.name);
+        check(&vector.name, &input, Some(expected[31] == 1));
+    }
+
+    // Further inputs, including malformed ones which must be rejected
+    let jeff1 = hex::decode(&vectors[0].input).unwrap();
+    check("jeff1 first pair", &jeff1[..PAIR_LEN], Some(false));
+    check("zeros", &[0u8; PAIR_LEN], Some(true));
+    check("not on curve", &[0x11u8; PAIR_LEN], None);
+    check("bad length", &[0x11u8; 79], None);
+    check("jeff1 truncated", &jeff1[..jeff1.len() - 1], None);
+}
+
+/// Random inputs, valid and malformed, give the same result as the
+/// precompile.
+#[test]
+fn test_eip197_differential() {
+    let outside = g2_outside_subgroup();
+    for i in 0..8 {
+        let (s1, s2) = (scalar("s1", i), scalar("s2", i));
+
+        // e(s1 P, s2 Q) e(-s1 s2 P, Q) = 1
+        let pairs = [(g1(s1), g2(s2)), (g1(-s1 * s2), G2Affine::generator())];
+        check("cancelling pairs", &encode(&pairs), Some(true));
+        let pairs = [(g1(s1), g2(s2)), (g1(s1 * s2), G2Affine::generator())];
+        check("non-cancelling pairs", &encode(&pairs), Some(false));
+
+        // The identity contributes nothing
+        let pairs = [
+            (g1(s1), G2Affine::identity()),
+            (G1Affine::identity(), g2(s2)),
+        ];
+        check("identity pairs", &encode(&pairs), Some(true));
+        let pairs = [(g1(s1), g2(s2)), (G1Affine::identity(), g2(s2))];
+        check("identity and non-identity", &encode(&pairs), Some(false));
+
+        // Malformed points
+        let valid = encode(&[(g1(s1), g2(s2))]);
+        let mut input = valid.clone();
+        input[63] ^= 1;
+        check("G1 off curve", &input, None);
+        let mut input = valid.clone();
+        input[191] ^= 1;
+        check("G2 off twist", &input, None);
+        let mut input = valid.clone();
+        input[..32].copy_from_slice(&Fq::MODULUS.to_bytes_be());
+        check("coordinate not reduced", &input, None);
+        check("G2 outside subgroup", &encode(&[(g1(s1), outside)]), None);
+    }
+}
+
+/// `verify_checked` accepts exactly the proofs the on-chain verifier does.
+#[test]
+fn test_verifier_differential() {
+    let Sample { vk, proof, inputs } = sample();
+
+    let mut evm = GasMeter::new();
+    let verifier = evm.deploy(&verifier_creation_code(&vk)).unwrap();
+    let mut check = |name: &str, proof: &Proof, inputs: &Inputs, expected: bool| {
+        let on_chain = evm.call(verifier, &verify_calldata(proof, inputs)).is_ok();
+        assert_eq!(on_chain, expected, "{name}: on-chain verifier");
+        assert_eq!(
+            verify_checked(&vk, proof, inputs).is_ok(),
+            expected,
+            "{name}: groth16"
+        );
+    };
+
+    check("valid", &proof, &inputs, true);
+    let rerandomized =
+        groth16::rerandomize::rerandomize(&vk, &proof, Fr::from(3u64), Fr::from(5u64)).unwrap();
+    check("rerandomized", &rerandomized, &inputs, true);
+
+    let mut bad_inputs = inputs.clone();
+    bad_inputs[0] += Fr::one();
+    check("wrong input", &proof, &bad_inputs, false);
+
+    let swapped = Proof {
+        pi_a: proof.pi_c,
+        pi_c: proof.pi_a,
+        ..proof.clone()
+    };
+    check("swapped A and C", &swapped, &inputs, false);
+    let negated = Proof {
+        pi_a: -proof.pi_a,
+        ..proof.clone()
+    };
+    check("negated A", &negated, &inputs, false);
+    let identity = Proof {
+        pi_c: G1Affine::identity(),
+        ..proof.clone()
+    };
+    check("identity C", &identity, &inputs, false);
+    let off_curve = Proof {
+        pi_a: G1Affine::new_unchecked(proof.pi_a.x, proof.pi_a.x),
+        ..proof.clone()
+    };
+    check("A off curve", &off_curve, &inputs, false);
+    let outside = Proof {
+        pi_b: g2_outside_subgroup(),
+        ..proof.clone()
+    };
+    check("B outside subgroup", &outside, &inputs, false);
+}
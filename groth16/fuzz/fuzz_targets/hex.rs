@@ -0,0 +1,16 @@
+#![no_main]
+
+use groth16::has_json_repr::le_bytes32_from_hex;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(bytes) = le_bytes32_from_hex(s) {
+        // The bytes are the big-endian value of the hex string, reversed.
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        assert!(digits.len() <= 64);
+        let mut be = bytes;
+        be.reverse();
+        let hex = format!("{:0>64}", digits.to_lowercase());
+        assert_eq!(hex, hex::encode(be));
+    }
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use groth16::has_json_repr::HasJsonRepr;
+use groth16::Inputs;
+use libfuzzer_sys::fuzz_target;
+
+// The JSON representation of the inputs is a list of strings, so generate
+// those directly to exercise the decimal and hex field element parsers.
+fuzz_target!(|repr: Vec<String>| {
+    if let Ok(inputs) = Inputs::from_json(&repr) {
+        let json = inputs.to_json();
+        assert_eq!(Inputs::from_json(&json).unwrap(), inputs);
+        // Decimal strings are preserved, up to leading zeros.
+        for (s, j) in repr.iter().zip(&json) {
+            let digits = s.trim_start_matches('0');
+            assert!(s.starts_with("0x") || digits == j || (digits.is_empty() && j == "0"));
+        }
+    }
+});
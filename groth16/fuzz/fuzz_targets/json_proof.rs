@@ -0,0 +1,13 @@
+#![no_main]
+
+use groth16::has_json_repr::{parse_json, HasJsonRepr};
+use groth16::Proof;
+use libfuzzer_sys::fuzz_target;
+
+// Parsing arbitrary bytes must not panic, and a parsed proof must survive a
+// round trip through its JSON representation.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(proof) = parse_json::<Proof>(data) {
+        assert_eq!(Proof::from_json(&proof.to_json()).unwrap(), proof);
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use groth16::has_json_repr::{parse_json, HasJsonRepr};
+use groth16::VerifyingKey;
+use libfuzzer_sys::fuzz_target;
+
+// Parsing arbitrary bytes must not panic, and a parsed key must survive a
+// round trip through its JSON representation.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(vk) = parse_json::<VerifyingKey>(data) {
+        assert_eq!(VerifyingKey::from_json(&vk.to_json()).unwrap(), vk);
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use groth16::batch::BatchEntry;
+use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::VerifyingKey;
+use libfuzzer_sys::fuzz_target;
+
+type VkRepr = <VerifyingKey as HasPrimitiveRepr>::Repr;
+type EntryRepr = <BatchEntry as HasPrimitiveRepr>::Repr;
+
+// Converting from an arbitrary representation must not panic, and any
+// representation which is accepted is the canonical one.
+fuzz_target!(|reprs: (VkRepr, EntryRepr)| {
+    let (vk_repr, entry_repr) = reprs;
+    if let Ok(vk) = VerifyingKey::try_from_repr(&vk_repr) {
+        assert_eq!(vk.to_repr(), vk_repr);
+        assert_eq!(VerifyingKey::from_repr(&vk_repr), vk);
+        let _ = vk.check();
+    }
+    if let Ok(entry) = BatchEntry::try_from_repr(&entry_repr) {
+        assert_eq!(entry.to_repr(), entry_repr);
+        assert_eq!(BatchEntry::from_repr(&entry_repr), entry);
+        let _ = entry.proof.check();
+    }
+});
@@ -0,0 +1,83 @@
+#![no_main]
+
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use groth16::batch::BatchEntry;
+use groth16::error::VerifyError;
+use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::{verify_checked, Inputs, Proof, VerifyingKey};
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+
+/// A verifying key, proof and inputs to verify.
+#[derive(Arbitrary, Debug)]
+enum Bundle {
+    /// Valid points, given as multiples of the generators (including the
+    /// point at infinity), with arbitrary inputs.
+    Scalars {
+        vk: [u64; 4],
+        s: Vec<u64>,
+        proof: [u64; 3],
+        inputs: Vec<u64>,
+    },
+    /// Arbitrary (usually invalid) representations.
+    Raw {
+        vk: Box<<VerifyingKey as HasPrimitiveRepr>::Repr>,
+        entry: Box<<BatchEntry as HasPrimitiveRepr>::Repr>,
+    },
+}
+
+fn g1(k: u64) -> G1Affine {
+    (G1Affine::generator() * Fr::from(k)).into_affine()
+}
+
+fn g2(k: u64) -> G2Affine {
+    (G2Affine::generator() * Fr::from(k)).into_affine()
+}
+
+// Verification must not panic, whatever the bundle.
+fuzz_target!(|bundle: Bundle| {
+    match bundle {
+        Bundle::Scalars {
+            vk,
+            s,
+            proof,
+            inputs,
+        } => {
+            let vk = VerifyingKey::new(g1(vk[0]), g2(vk[1]), g2(vk[2]), g2(vk[3]), {
+                s.iter().map(|k| g1(*k)).collect()
+            });
+            let proof = Proof::new(g1(proof[0]), g2(proof[1]), g1(proof[2])).unwrap();
+            let inputs: Inputs = inputs.into_iter().map(Fr::from).collect();
+            let vk = match vk {
+                Ok(vk) => vk,
+                Err(e) => {
+                    assert!(s.is_empty());
+                    assert_eq!(e, VerifyError::InvalidVerifyingKey);
+                    return;
+                }
+            };
+            let result = verify_checked(&vk, &proof, &inputs);
+            if inputs.len() != vk.num_inputs() {
+                assert_eq!(
+                    result,
+                    Err(VerifyError::InputLengthMismatch {
+                        expected: vk.num_inputs(),
+                        actual: inputs.len(),
+                    })
+                );
+            }
+        }
+        Bundle::Raw { vk, entry } => {
+            let (Ok(vk), Ok(entry)) = (
+                VerifyingKey::try_from_repr(&vk),
+                BatchEntry::try_from_repr(&entry),
+            ) else {
+                return;
+            };
+            if vk.check().is_ok() {
+                let _ = verify_checked(&vk, &entry.proof, &entry.inputs);
+            }
+        }
+    }
+});
@@ -0,0 +1,215 @@
+use crate::has_json_repr::{try_load_json, try_read_jsonl};
+use crate::rerandomize::rerandomize;
+use crate::{Inputs, Proof, VerifyingKey};
+use anyhow::{Error, Result};
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use std::collections::{BTreeMap, HashMap};
+
+/// A proof generated by an application, with its public inputs and a reference
+/// to its verification key in a `VkTable`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApplicationProof {
+    /// Identifies the application circuit that generated the proof
+    pub circuit_id: String,
+    /// Key of the verification key in the `VkTable`
+    pub vk_ref: String,
+    pub proof: Proof,
+    pub inputs: Inputs,
+}
+
+/// Verification keys, by reference
+pub type VkTable = BTreeMap<String, VerifyingKey>;
+
+/// A proof to be verified as part of a batch
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchEntry {
+    /// Index of the verification key in `Batch::vks`
+    pub vk_index: u32,
+    pub inputs: Inputs,
+    pub proof: Proof,
+}
+
+/// A batch of proofs, with the distinct verification keys they use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Batch {
+    pub vks: Vec<VerifyingKey>,
+    pub entries: Vec<BatchEntry>,
+}
+
+impl Batch {
+    /// A batch verifying the same proof `batch_size` times.
+    pub fn repeated(vk: VerifyingKey, inputs: Inputs, proof: Proof, batch_size: u32) -> Self {
+        let entry = BatchEntry {
+            vk_index: 0,
+            inputs,
+            proof,
+        };
+        Self {
+            vks: vec![vk],
+            entries: vec![entry; batch_size as usize],
+        }
+    }
+
+    /// A batch of `batch_size` distinct proofs, each obtained by
+    /// re-randomising `proof` with scalars derived from `seed`, so the batch
+    /// is deterministic.
+    pub fn rerandomized(
+        vk: VerifyingKey,
+        inputs: Inputs,
+        proof: Proof,
+        batch_size: u32,
+        seed: u64,
+    ) -> Self {
+        let mut scalars = ScalarStream(seed);
+        let entries = (0..batch_size)
+            .map(|_| BatchEntry {
+                vk_index: 0,
+                inputs: inputs.clone(),
+                // `r1` is zero with negligible probability, so this loop
+                // almost never repeats.
+                proof: loop {
+                    let (r1, r2) = (scalars.next_scalar(), scalars.next_scalar());
+                    if let Some(proof) = rerandomize(&vk, &proof, r1, r2) {
+                        break proof;
+                    }
+                },
+            })
+            .collect();
+        Self {
+            vks: vec![vk],
+            entries,
+        }
+    }
+
+    /// A batch verifying each of `proofs`, with the keys they refer to taken
+    /// from `vk_table`. Only the keys which are used are included, once each.
+    pub fn from_application_proofs(
+        vk_table: &VkTable,
+        proofs: &[ApplicationProof],
+    ) -> Result<Self> {
+        let mut vk_indices: HashMap<&str, u32> = HashMap::new();
+        let mut batch = Self {
+            vks: Vec::new(),
+            entries: Vec::with_capacity(proofs.len()),
+        };
+        for proof in proofs {
+            let vk_index = match vk_indices.get(proof.vk_ref.as_str()) {
+                Some(&index) => index,
+                None => {
+                    let vk = vk_table.get(&proof.vk_ref).ok_or_else(|| {
+                        Error::msg(format!(
+                            "{}: unknown verification key {}",
+                            proof.circuit_id, proof.vk_ref
+                        ))
+                    })?;
+                    batch.vks.push(vk.clone());
+                    let index = batch.vks.len() as u32 - 1;
+                    vk_indices.insert(&proof.vk_ref, index);
+                    index
+                }
+            };
+            batch.entries.push(BatchEntry {
+                vk_index,
+                inputs: proof.inputs.clone(),
+                proof: proof.proof.clone(),
+            });
+        }
+        Ok(batch)
+    }
+
+    /// Load a batch from a JSON Lines file of `ApplicationProof`s and the JSON
+    /// `VkTable` they refer to.
+    pub fn load(proofs_path: &str, vk_table_path: &str) -> Result<Self> {
+        let vk_table: VkTable = try_load_json(vk_table_path)?;
+        let proofs: Vec<ApplicationProof> = try_read_jsonl(proofs_path)?;
+        Self::from_application_proofs(&vk_table, &proofs)
+    }
+
+    /// Number of proofs in the batch
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Deterministic pseudo-random scalars from the splitmix64 generator. These
+/// are not suitable for hiding anything, only for producing distinct proofs.
+struct ScalarStream(u64);
+
+impl ScalarStream {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_scalar(&mut self) -> Fr {
+        let mut bytes = [0u8; 32];
+        for chunk in bytes.chunks_exact_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes());
+        }
+        Fr::from_le_bytes_mod_order(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::has_json_repr::{read_jsonl, write_jsonl};
+    use crate::has_primitive_repr::HasPrimitiveRepr;
+    use crate::sample::{
+        sample, sample_batch, Sample, SAMPLE_APPLICATION_PROOFS_FILE, SAMPLE_VK_TABLE_FILE,
+    };
+    use crate::verify;
+
+    #[test]
+    fn test_application_proofs() {
+        let batch = Batch::load(SAMPLE_APPLICATION_PROOFS_FILE, SAMPLE_VK_TABLE_FILE).unwrap();
+        assert_eq!(batch.vks.len(), 1);
+        assert_eq!(batch.len(), 2);
+        for entry in &batch.entries {
+            let vk = &batch.vks[entry.vk_index as usize];
+            assert!(verify(vk, &entry.proof, &entry.inputs).is_ok());
+        }
+
+        assert_eq!(batch, sample_batch(2));
+        assert_eq!(batch, Batch::from_repr(&batch.to_repr()));
+
+        // Round trip through JSON Lines
+        let proofs: Vec<ApplicationProof> = read_jsonl(SAMPLE_APPLICATION_PROOFS_FILE);
+        let path = std::env::temp_dir().join("groth16_application_proofs.jsonl");
+        let path = path.to_str().unwrap();
+        write_jsonl(path, &proofs);
+        assert_eq!(read_jsonl::<ApplicationProof>(path), proofs);
+
+        // Unknown verification key
+        let vk_table = VkTable::new();
+        assert!(Batch::from_application_proofs(&vk_table, &proofs).is_err());
+    }
+
+    #[test]
+    fn test_rerandomized() {
+        let Sample { vk, proof, inputs } = sample();
+
+        let batch = Batch::rerandomized(vk.clone(), inputs.clone(), proof.clone(), 3, 7);
+        for (i, entry) in batch.entries.iter().enumerate() {
+            assert_ne!(entry.proof, proof);
+            for other in &batch.entries[..i] {
+                assert_ne!(entry.proof, other.proof);
+            }
+            assert!(verify(&batch.vks[0], &entry.proof, &entry.inputs).is_ok());
+        }
+
+        // Deterministic for a given seed
+        let again = Batch::rerandomized(vk.clone(), inputs.clone(), proof.clone(), 3, 7);
+        assert_eq!(again, batch);
+        let other_seed = Batch::rerandomized(vk, inputs, proof, 3, 8);
+        assert_ne!(other_seed.entries[0], batch.entries[0]);
+    }
+}
@@ -0,0 +1,73 @@
+//! Generate a Groth16 verifying key and proofs for a synthetic circuit.
+//!
+//! ```sh
+//! cargo run --release --features fixtures --bin generate_fixtures -- --inputs 4 --proofs 2
+//! ```
+//!
+//! Writes `vk.json`, `proof.json` and `inputs.json` (for the first proof), and
+//! all the proofs as `application_proofs.jsonl` with `vk_table.json`.
+use clap::Parser;
+use groth16::batch::{ApplicationProof, VkTable};
+use groth16::fixtures::generate;
+use groth16::has_json_repr::{save_json, write_jsonl};
+use std::path::PathBuf;
+
+/// The arguments for the generate_fixtures command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct GenerateArgs {
+    /// Number of public inputs of the circuit
+    #[clap(long = "inputs", default_value_t = 4)]
+    num_inputs: usize,
+
+    /// Number of proofs to generate
+    #[clap(long = "proofs", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    num_proofs: u32,
+
+    /// Seed for the setup and the proofs
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Directory to write the fixtures to (created if it does not exist)
+    #[clap(long, default_value = "./fixtures")]
+    output_dir: PathBuf,
+}
+
+fn main() {
+    let GenerateArgs {
+        num_inputs,
+        num_proofs,
+        seed,
+        output_dir,
+    } = GenerateArgs::parse();
+    let num_proofs = num_proofs as usize;
+
+    let fixtures = generate(num_inputs, num_proofs, seed);
+
+    std::fs::create_dir_all(&output_dir).unwrap();
+    let path = |name: &str| output_dir.join(name).to_str().unwrap().to_string();
+    let (proof, inputs) = &fixtures.proofs[0];
+    save_json(&path("vk.json"), &fixtures.vk);
+    save_json(&path("proof.json"), proof);
+    save_json(&path("inputs.json"), inputs);
+
+    let circuit_id = format!("squares_{num_inputs}");
+    let application_proofs: Vec<ApplicationProof> = fixtures
+        .proofs
+        .iter()
+        .map(|(proof, inputs)| ApplicationProof {
+            circuit_id: circuit_id.clone(),
+            vk_ref: circuit_id.clone(),
+            proof: proof.clone(),
+            inputs: inputs.clone(),
+        })
+        .collect();
+    let vk_table = VkTable::from([(circuit_id, fixtures.vk)]);
+    save_json(&path("vk_table.json"), &vk_table);
+    write_jsonl(&path("application_proofs.jsonl"), &application_proofs);
+
+    println!(
+        "Wrote {num_proofs} proofs with {num_inputs} public inputs to {}",
+        output_dir.display()
+    );
+}
@@ -0,0 +1,121 @@
+//! Cycle count baselines, shared by the cycle count tests of the zkVM guests.
+//!
+//! Each test executes its guest for several runs (labelled by batch size),
+//! and checks the cycles of each phase against the baseline saved as JSON
+//! next to the test. Set `UPDATE_CYCLE_BASELINE` to save the measured cycles
+//! as the new baseline instead.
+
+use std::collections::BTreeMap;
+
+/// Batch sizes at which the guests are measured
+pub const BATCH_SIZES: [u32; 4] = [1, 2, 4, 8];
+
+/// Allowed relative increase over the baseline
+pub const TOLERANCE: f64 = 0.01;
+
+/// Cycles per phase
+pub type Cycles = BTreeMap<String, u64>;
+
+/// Cycles per phase, for each run
+pub type Baseline = BTreeMap<String, Cycles>;
+
+/// Check `measured` against the baseline in `path`, panicking with the list of
+/// phases which have become more than `TOLERANCE` more expensive or have no
+/// baseline. Phases which have become cheaper are printed. With
+/// `UPDATE_CYCLE_BASELINE` set, `measured` is saved as the new baseline
+/// instead.
+pub fn check_cycle_baseline(path: &str, measured: &Baseline) {
+    if std::env::var("UPDATE_CYCLE_BASELINE").is_ok() {
+        let json = serde_json::to_string_pretty(measured).unwrap();
+        std::fs::write(path, json + "\n").unwrap();
+        return;
+    }
+
+    let baseline: Baseline = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let (regressions, improvements) = compare(&baseline, measured);
+    for improvement in improvements {
+        println!("{improvement}. Consider updating the baseline.");
+    }
+    assert!(
+        regressions.is_empty(),
+        "cycle count regressions:\n{}\n\
+         Run with UPDATE_CYCLE_BASELINE=1 to record a new baseline.",
+        regressions.join("\n")
+    );
+}
+
+/// The phases of `measured` which are more expensive than in `baseline`, or
+/// have no baseline, and those which are cheaper.
+fn compare(baseline: &Baseline, measured: &Baseline) -> (Vec<String>, Vec<String>) {
+    let mut regressions = Vec::new();
+    let mut improvements = Vec::new();
+    for (run, cycles) in measured {
+        for (phase, &count) in cycles {
+            match baseline.get(run).and_then(|b| b.get(phase)) {
+                Some(&expected) if count as f64 > expected as f64 * (1.0 + TOLERANCE) => {
+                    regressions.push(format!(
+                        "batch size {run}, {phase}: {count} cycles (baseline {expected})"
+                    ));
+                }
+                Some(&expected) if (count as f64) < expected as f64 * (1.0 - TOLERANCE) => {
+                    improvements.push(format!(
+                        "batch size {run}, {phase}: {count} cycles, down from {expected}"
+                    ));
+                }
+                Some(_) => {}
+                None => regressions.push(format!(
+                    "batch size {run}, {phase}: {count} cycles (no baseline)"
+                )),
+            }
+        }
+    }
+    (regressions, improvements)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn baseline(runs: &[(&str, &str, u64)]) -> Baseline {
+        let mut baseline = Baseline::new();
+        for &(run, phase, count) in runs {
+            baseline
+                .entry(run.to_string())
+                .or_default()
+                .insert(phase.to_string(), count);
+        }
+        baseline
+    }
+
+    #[test]
+    fn test_compare() {
+        let expected = baseline(&[("1", "verify", 1000), ("1", "read", 100)]);
+
+        // Within the tolerance either way
+        let measured = baseline(&[("1", "verify", 1010), ("1", "read", 99)]);
+        assert_eq!(compare(&expected, &measured), (vec![], vec![]));
+
+        // More expensive, cheaper, and missing from the baseline
+        let measured = baseline(&[
+            ("1", "verify", 1011),
+            ("1", "read", 98),
+            ("2", "verify", 2000),
+        ]);
+        let (regressions, improvements) = compare(&expected, &measured);
+        assert_eq!(
+            regressions,
+            vec![
+                "batch size 1, verify: 1011 cycles (baseline 1000)",
+                "batch size 2, verify: 2000 cycles (no baseline)",
+            ]
+        );
+        assert_eq!(
+            improvements,
+            vec!["batch size 1, read: 98 cycles, down from 100"]
+        );
+
+        // An empty baseline has no entry for any phase
+        let (regressions, _) = compare(&Baseline::new(), &measured);
+        assert_eq!(regressions.len(), 3);
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Reason for rejecting a proof, its public inputs or its verifying key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerifyError {
+    /// The number of public inputs does not match the verifying key
+    InputLengthMismatch { expected: usize, actual: usize },
+    /// A field element could not be parsed
+    InvalidFieldElement,
+    /// A field element is not reduced modulo the field order
+    NonCanonicalFieldElement,
+    /// A point is not on the curve
+    PointNotOnCurve,
+    /// A point is on the curve but not in the prime-order subgroup
+    PointNotInSubgroup,
+    /// The verifying key has no input commitments (`s` is empty)
+    InvalidVerifyingKey,
+    /// The pairing check failed, i.e. the proof is invalid
+    PairingMismatch,
+    /// The final exponentiation of the pairing could not be computed
+    PairingFailed,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InputLengthMismatch { expected, actual } => {
+                write!(f, "expected {expected} public inputs, got {actual}")
+            }
+            VerifyError::InvalidFieldElement => write!(f, "invalid field element"),
+            VerifyError::NonCanonicalFieldElement => write!(f, "non-canonical field element"),
+            VerifyError::PointNotOnCurve => write!(f, "point not on curve"),
+            VerifyError::PointNotInSubgroup => write!(f, "point not in subgroup"),
+            VerifyError::InvalidVerifyingKey => write!(f, "verifying key has no input commitments"),
+            VerifyError::PairingMismatch => write!(f, "pairing check failed"),
+            VerifyError::PairingFailed => write!(f, "pairing computation failed"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
@@ -0,0 +1,117 @@
+//! Groth16 test fixtures, generated with the arkworks prover.
+//!
+//! The synthetic circuit has one public input per square: it proves knowledge
+//! of a witness `w_i` with `w_i * w_i = x_i` for each public input `x_i`. This
+//! gives a verifying key and proofs of the same shape as those of a real
+//! application with that number of public inputs.
+
+use crate::{Inputs, Proof, VerifyingKey};
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::Groth16;
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use ark_std::UniformRand;
+
+/// A verifying key and proofs (with their public inputs) for it.
+pub struct Fixtures {
+    pub vk: VerifyingKey,
+    pub proofs: Vec<(Proof, Inputs)>,
+}
+
+/// Proves knowledge of a square root of each public input.
+struct SquaresCircuit {
+    roots: Vec<Fr>,
+}
+
+impl ConstraintSynthesizer<Fr> for SquaresCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        for root in self.roots {
+            let x = cs.new_input_variable(|| Ok(root * root))?;
+            let w = cs.new_witness_variable(|| Ok(root))?;
+            cs.enforce_constraint(lc!() + w, lc!() + w, lc!() + x)?;
+        }
+        // Ensure there is at least one constraint, even with no inputs.
+        let w = cs.new_witness_variable(|| Ok(Fr::from(1u64)))?;
+        cs.enforce_constraint(lc!() + w, lc!() + w, lc!() + w)?;
+        Ok(())
+    }
+}
+
+/// Generate a verifying key for `num_inputs` public inputs, and `num_proofs`
+/// proofs for distinct random inputs. The output depends only on the
+/// arguments.
+pub fn generate(num_inputs: usize, num_proofs: usize, seed: u64) -> Fixtures {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let setup_circuit = SquaresCircuit {
+        roots: vec![Fr::from(0u64); num_inputs],
+    };
+    let pk = Groth16::<Bn254>::generate_random_parameters_with_reduction(setup_circuit, &mut rng)
+        .unwrap();
+
+    let proofs = (0..num_proofs)
+        .map(|_| {
+            let roots: Vec<Fr> = (0..num_inputs).map(|_| Fr::rand(&mut rng)).collect();
+            let inputs: Inputs = roots.iter().map(|root| root * root).collect();
+            let proof = Groth16::<Bn254>::create_random_proof_with_reduction(
+                SquaresCircuit { roots },
+                &pk,
+                &mut rng,
+            )
+            .unwrap();
+            (Proof::from(proof), inputs)
+        })
+        .collect();
+
+    Fixtures {
+        vk: VerifyingKey::from(pk.vk),
+        proofs,
+    }
+}
+
+impl From<ark_groth16::VerifyingKey<Bn254>> for VerifyingKey {
+    fn from(vk: ark_groth16::VerifyingKey<Bn254>) -> Self {
+        Self {
+            alpha: vk.alpha_g1,
+            beta: vk.beta_g2,
+            gamma: vk.gamma_g2,
+            delta: vk.delta_g2,
+            s: vk.gamma_abc_g1,
+        }
+    }
+}
+
+impl From<ark_groth16::Proof<Bn254>> for Proof {
+    fn from(proof: ark_groth16::Proof<Bn254>) -> Self {
+        Self {
+            pi_a: proof.a,
+            pi_b: proof.b,
+            pi_c: proof.c,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::verify;
+
+    #[test]
+    fn test_fixtures() {
+        let fixtures = generate(3, 2, 7);
+        assert_eq!(fixtures.vk.s.len(), 4);
+        for (proof, inputs) in &fixtures.proofs {
+            assert_eq!(inputs.len(), 3);
+            assert!(verify(&fixtures.vk, proof, inputs).is_ok());
+        }
+        assert_ne!(fixtures.proofs[0], fixtures.proofs[1]);
+
+        // Deterministic for a given seed
+        let again = generate(3, 2, 7);
+        assert_eq!(again.vk, fixtures.vk);
+        assert_eq!(again.proofs, fixtures.proofs);
+        assert_ne!(generate(3, 1, 8).vk, fixtures.vk);
+    }
+}
@@ -1,13 +1,16 @@
-use crate::{Proof, VerifyingKey};
-use anyhow::Error;
-use anyhow::Result;
+use crate::batch::ApplicationProof;
+use crate::error::VerifyError;
+use crate::validate::{fp_from_bigint, g1_from_xy, g2_from_xy};
+use crate::{Inputs, Proof, VerifyingKey};
+use anyhow::{Context, Error, Result};
 use ark_bn254::{G1Affine, G2Affine};
 use ark_ec::models::short_weierstrass::Affine;
-use ark_ff::{BigInt, Fp, Fp2, Fp2Config, FpConfig};
+use ark_ff::{BigInt, Field, Fp, Fp2, Fp2Config, FpConfig};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::array::TryFromSliceError;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::str::FromStr;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 
 /// Types with a Json representation
 pub trait HasJsonRepr: Sized {
@@ -18,12 +21,81 @@ pub trait HasJsonRepr: Sized {
     fn from_json(repr: &Self::JsonRepr) -> Result<Self>;
 }
 
-pub fn load_json<T>(path: &str) -> T
-where
-    T: HasJsonRepr,
-    T::JsonRepr: DeserializeOwned,
-{
-    HasJsonRepr::from_json(&serde_json::from_reader(File::open(path).unwrap()).unwrap()).unwrap()
+/// Load a `T` from a JSON file, panicking on error. See `try_load_json`.
+pub fn load_json<T: HasJsonRepr>(path: &str) -> T {
+    try_load_json(path).unwrap_or_else(|e| panic!("{e:#}"))
+}
+
+/// Load a `T` from a JSON file. Errors give the path and, for invalid
+/// values, the field.
+pub fn try_load_json<T: HasJsonRepr>(path: &str) -> Result<T> {
+    let file = File::open(path).map_err(|e| Error::msg(format!("failed to open {path}: {e}")))?;
+    read_json(BufReader::new(file)).with_context(|| format!("failed to load {path}"))
+}
+
+/// Read a `T` from JSON.
+pub fn read_json<T: HasJsonRepr, R: Read>(reader: R) -> Result<T> {
+    let repr =
+        serde_json::from_reader(reader).map_err(|e| Error::msg(format!("invalid JSON: {e}")))?;
+    T::from_json(&repr)
+}
+
+/// Parse a `T` from JSON bytes.
+pub fn parse_json<T: HasJsonRepr>(bytes: &[u8]) -> Result<T> {
+    let repr =
+        serde_json::from_slice(bytes).map_err(|e| Error::msg(format!("invalid JSON: {e}")))?;
+    T::from_json(&repr)
+}
+
+pub fn save_json<T: HasJsonRepr>(path: &str, value: &T) {
+    serde_json::to_writer_pretty(File::create(path).unwrap(), &value.to_json()).unwrap()
+}
+
+/// Read a JSON Lines file, with one `T` per (non-empty) line, panicking on
+/// error. See `try_read_jsonl`.
+pub fn read_jsonl<T: HasJsonRepr>(path: &str) -> Vec<T> {
+    try_read_jsonl(path).unwrap_or_else(|e| panic!("{e:#}"))
+}
+
+/// Read a JSON Lines file, with one `T` per (non-empty) line. Errors give the
+/// path, line number and, for invalid values, the field.
+pub fn try_read_jsonl<T: HasJsonRepr>(path: &str) -> Result<Vec<T>> {
+    let file = File::open(path).map_err(|e| Error::msg(format!("failed to open {path}: {e}")))?;
+    let mut values = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| Error::msg(format!("failed to read {path}: {e}")))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value = parse_json(line.as_bytes())
+            .with_context(|| format!("failed to load {path}, line {}", i + 1))?;
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Write `values` as a JSON Lines file, with one `T` per line.
+pub fn write_jsonl<T: HasJsonRepr>(path: &str, values: &[T]) {
+    let mut writer = BufWriter::new(File::create(path).unwrap());
+    for value in values {
+        serde_json::to_writer(&mut writer, &value.to_json()).unwrap();
+        writer.write_all(b"\n").unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+impl<T: HasJsonRepr> HasJsonRepr for BTreeMap<String, T> {
+    type JsonRepr = BTreeMap<String, T::JsonRepr>;
+
+    fn to_json(&self) -> Self::JsonRepr {
+        self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect()
+    }
+
+    fn from_json(repr: &Self::JsonRepr) -> Result<Self> {
+        repr.iter()
+            .map(|(k, v)| Ok((k.clone(), T::from_json(v).with_context(|| k.clone())?)))
+            .collect()
+    }
 }
 
 impl<T: HasJsonRepr> HasJsonRepr for Vec<T> {
@@ -34,7 +106,10 @@ impl<T: HasJsonRepr> HasJsonRepr for Vec<T> {
     }
 
     fn from_json(repr: &Self::JsonRepr) -> Result<Self> {
-        repr.iter().map(T::from_json).collect::<Result<Vec<_>, _>>()
+        repr.iter()
+            .enumerate()
+            .map(|(i, v)| T::from_json(v).with_context(|| format!("[{i}]")))
+            .collect::<Result<Vec<_>, _>>()
     }
 }
 
@@ -56,10 +131,15 @@ fn u64_from_le_bytes_slice(bytes: &[u8]) -> Result<u64> {
     Ok(u64_from_le_bytes(&bytes?))
 }
 
-fn le_bytes32_from_hex(s: &str) -> Result<[u8; 32], String> {
+/// Parse a hex string (with or without a leading "0x") of 1 to 32 bytes as a
+/// little-endian byte array.
+pub fn le_bytes32_from_hex(s: &str) -> Result<[u8; 32], String> {
     fn from_sanitized_hex(s: &str) -> Result<[u8; 32], String> {
-        let hex_bytes = hex::decode(s).unwrap_or_else(|e| panic!("invalid hex: {e}"));
+        let hex_bytes = hex::decode(s).map_err(|e| format!("invalid hex: {e}"))?;
         let num_bytes = hex_bytes.len();
+        if num_bytes > 32 {
+            return Err(format!("{num_bytes} bytes is too long (at most 32)"));
+        }
         let byte_offset = 32 - num_bytes;
         let mut bytes = [0u8; 32];
         bytes[byte_offset..].clone_from_slice(&hex_bytes);
@@ -74,6 +154,9 @@ fn le_bytes32_from_hex(s: &str) -> Result<[u8; 32], String> {
         s
     };
 
+    if s.is_empty() {
+        return Err("no hex digits".to_string());
+    }
     if s.len() % 2 == 0 {
         from_sanitized_hex(s)
     } else {
@@ -85,20 +168,50 @@ fn le_bytes32_from_hex(s: &str) -> Result<[u8; 32], String> {
     }
 }
 
-impl<P: FpConfig<4>> HasJsonRepr for Fp<P, 4>
-where
-    Fp<P, 4>: FromStr,
-{
+/// Parse a string of decimal digits (leading zeros are allowed) as an
+/// integer, which must fit in 256 bits. Anything other than digits, including
+/// signs and separators, is an `InvalidFieldElement`.
+pub(crate) fn bigint_from_decimal(s: &str) -> Result<BigInt<4>, VerifyError> {
+    if s.is_empty() {
+        return Err(VerifyError::InvalidFieldElement);
+    }
+    let mut limbs = [0u64; 4];
+    for c in s.bytes() {
+        if !c.is_ascii_digit() {
+            return Err(VerifyError::InvalidFieldElement);
+        }
+        let mut carry = (c - b'0') as u128;
+        for limb in limbs.iter_mut() {
+            let v = (*limb as u128) * 10 + carry;
+            *limb = v as u64;
+            carry = v >> 64;
+        }
+        if carry != 0 {
+            return Err(VerifyError::NonCanonicalFieldElement);
+        }
+    }
+    Ok(BigInt(limbs))
+}
+
+impl<P: FpConfig<4>> HasJsonRepr for Fp<P, 4> {
     type JsonRepr = String;
 
     fn to_json(&self) -> Self::JsonRepr {
-        self.to_string()
+        // `Display` trims all leading zeros, giving "" for zero
+        let repr = self.to_string();
+        if repr.is_empty() {
+            "0".to_string()
+        } else {
+            repr
+        }
     }
 
     fn from_json(repr: &Self::JsonRepr) -> Result<Self> {
         if repr.starts_with("0x") {
-            let bytes = le_bytes32_from_hex(repr)
-                .map_err(|e| Error::msg(format!("Failed to parse Fr hex string: {repr}: {e}")))?;
+            let bytes = le_bytes32_from_hex(repr).map_err(|e| {
+                Error::msg(VerifyError::InvalidFieldElement)
+                    .context(format!("Failed to parse Fr hex string: {repr}: {e}"))
+            })?;
             let b: Result<BigInt<4>, TryFromSliceError> = {
                 Ok(BigInt::<4>([
                     u64_from_le_bytes_slice(&bytes[0..8])?,
@@ -108,25 +221,73 @@ where
                 ]))
             };
             let b = b.map_err(|e| Error::msg(format!("try from slice: {e}")))?;
-            P::from_bigint(b).ok_or_else(|| Error::msg("failed converting to bigint"))
+            fp_from_bigint(b)
+                .map_err(|e| Error::msg(e).context(format!("Field element out of range: {repr}")))
         } else {
-            Self::from_str(repr).map_err(|_| Error::msg("Failed to parse Fr hex string: {repr}"))
+            // Not `from_str`, which reduces values modulo the field order
+            // rather than rejecting them.
+            let b = bigint_from_decimal(repr).map_err(|e| {
+                Error::msg(e).context(format!("Failed to parse Fr decimal string: {repr}"))
+            })?;
+            fp_from_bigint(b)
+                .map_err(|e| Error::msg(e).context(format!("Field element out of range: {repr}")))
+        }
+    }
+}
+
+/// Affine coordinates of a point from its JSON representation, or `None` for
+/// the point at infinity. Points are either `[x, y]`, with `[0, 0]` for the
+/// point at infinity, or (as output by snarkjs) `[x, y, z]` with `z = 1`, or
+/// `[0, 1, 0]` for the point at infinity.
+fn xy_from_json<F>(repr: &[F::JsonRepr]) -> Result<Option<(F, F)>>
+where
+    F: HasJsonRepr + Field,
+{
+    let (x, y) = match repr {
+        [x, y] => (F::from_json(x)?, F::from_json(y)?),
+        [x, y, z] => {
+            let (x, y, z) = (F::from_json(x)?, F::from_json(y)?, F::from_json(z)?);
+            if z.is_zero() {
+                if !x.is_zero() || !y.is_one() {
+                    return Err(Error::msg(
+                        "projective points at infinity must be [0, 1, 0]",
+                    ));
+                }
+                return Ok(None);
+            }
+            if !z.is_one() {
+                return Err(Error::msg("projective points must have z = 0 or z = 1"));
+            }
+            (x, y)
+        }
+        _ => {
+            return Err(Error::msg(format!(
+                "points must have 2 or 3 coordinates, got {}",
+                repr.len()
+            )))
         }
+    };
+    if x.is_zero() && y.is_zero() {
+        return Ok(None);
     }
+    Ok(Some((x, y)))
 }
 
 impl HasJsonRepr for Affine<ark_bn254::g1::Config> {
     type JsonRepr = G1AffineJson;
     fn to_json(&self) -> Self::JsonRepr {
-        assert!(!self.infinity);
-        [self.x.to_json(), self.y.to_json()]
+        if self.infinity {
+            return vec![ark_bn254::Fq::ZERO.to_json(), ark_bn254::Fq::ZERO.to_json()];
+        }
+        vec![self.x.to_json(), self.y.to_json()]
     }
     fn from_json(repr: &Self::JsonRepr) -> Result<Self> {
-        Ok(Self {
-            x: ark_bn254::Fq::from_json(&repr[0])?,
-            y: ark_bn254::Fq::from_json(&repr[1])?,
-            infinity: false,
-        })
+        match xy_from_json(repr)? {
+            Some((x, y)) => {
+                g1_from_xy(x, y).map_err(|e| Error::msg(e).context(format!("G1 point {repr:?}")))
+            }
+            None => Ok(Self::identity()),
+        }
     }
 }
 
@@ -144,8 +305,8 @@ where
 
     fn from_json(repr: &Self::JsonRepr) -> Result<Self> {
         Ok(Self {
-            c0: P::Fp::from_json(&repr[0])?,
-            c1: P::Fp::from_json(&repr[1])?,
+            c0: P::Fp::from_json(&repr[0]).context("c0")?,
+            c1: P::Fp::from_json(&repr[1]).context("c1")?,
         })
     }
 }
@@ -153,20 +314,28 @@ where
 impl HasJsonRepr for Affine<ark_bn254::g2::Config> {
     type JsonRepr = G2AffineJson;
     fn to_json(&self) -> Self::JsonRepr {
-        assert!(!self.infinity);
-        [self.x.to_json(), self.y.to_json()]
+        if self.infinity {
+            return vec![
+                ark_bn254::Fq2::ZERO.to_json(),
+                ark_bn254::Fq2::ZERO.to_json(),
+            ];
+        }
+        vec![self.x.to_json(), self.y.to_json()]
     }
     fn from_json(repr: &Self::JsonRepr) -> Result<Self> {
-        Ok(Self {
-            x: ark_bn254::Fq2::from_json(&repr[0])?,
-            y: ark_bn254::Fq2::from_json(&repr[1])?,
-            infinity: false,
-        })
+        match xy_from_json(repr)? {
+            Some((x, y)) => {
+                g2_from_xy(x, y).map_err(|e| Error::msg(e).context(format!("G2 point {repr:?}")))
+            }
+            None => Ok(Self::identity()),
+        }
     }
 }
 
-pub type G1AffineJson = [String; 2];
-pub type G2AffineJson = [[String; 2]; 2];
+/// `[x, y]`, or `[x, y, z]` (see `xy_from_json`)
+pub type G1AffineJson = Vec<String>;
+/// `[x, y]`, or `[x, y, z]` (see `xy_from_json`)
+pub type G2AffineJson = Vec<Fp2Json>;
 
 #[derive(Serialize, Deserialize)]
 pub struct VerifyingKeyJson {
@@ -189,13 +358,18 @@ impl HasJsonRepr for VerifyingKey {
         }
     }
     fn from_json(json: &Self::JsonRepr) -> Result<Self> {
-        Ok(Self {
-            alpha: G1Affine::from_json(&json.alpha)?,
-            beta: G2Affine::from_json(&json.beta)?,
-            gamma: G2Affine::from_json(&json.gamma)?,
-            delta: G2Affine::from_json(&json.delta)?,
-            s: Vec::<G1Affine>::from_json(&json.s)?,
-        })
+        // Each point is checked as it is parsed.
+        let vk = Self::new_unchecked(
+            G1Affine::from_json(&json.alpha).context("alpha")?,
+            G2Affine::from_json(&json.beta).context("beta")?,
+            G2Affine::from_json(&json.gamma).context("gamma")?,
+            G2Affine::from_json(&json.delta).context("delta")?,
+            Vec::<G1Affine>::from_json(&json.s).context("s")?,
+        );
+        if vk.s.is_empty() {
+            return Err(Error::msg(VerifyError::InvalidVerifyingKey));
+        }
+        Ok(vk)
     }
 }
 
@@ -217,9 +391,190 @@ impl HasJsonRepr for Proof {
     }
     fn from_json(json: &Self::JsonRepr) -> Result<Self> {
         Ok(Self {
-            pi_a: G1Affine::from_json(&json.pi_a)?,
-            pi_b: G2Affine::from_json(&json.pi_b)?,
-            pi_c: G1Affine::from_json(&json.pi_c)?,
+            pi_a: G1Affine::from_json(&json.pi_a).context("pi_a")?,
+            pi_b: G2Affine::from_json(&json.pi_b).context("pi_b")?,
+            pi_c: G1Affine::from_json(&json.pi_c).context("pi_c")?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ApplicationProofJson {
+    pub circuit_id: String,
+    pub vk_ref: String,
+    pub proof: ProofJson,
+    pub inputs: <Inputs as HasJsonRepr>::JsonRepr,
+}
+
+impl HasJsonRepr for ApplicationProof {
+    type JsonRepr = ApplicationProofJson;
+    fn to_json(&self) -> Self::JsonRepr {
+        Self::JsonRepr {
+            circuit_id: self.circuit_id.clone(),
+            vk_ref: self.vk_ref.clone(),
+            proof: self.proof.to_json(),
+            inputs: self.inputs.to_json(),
+        }
+    }
+    fn from_json(json: &Self::JsonRepr) -> Result<Self> {
+        Ok(Self {
+            circuit_id: json.circuit_id.clone(),
+            vk_ref: json.vk_ref.clone(),
+            proof: Proof::from_json(&json.proof).context("proof")?,
+            inputs: Inputs::from_json(&json.inputs).context("inputs")?,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sample::{
+        sample, SAMPLE_APPLICATION_PROOFS_FILE, SAMPLE_PROOF_FILE, SAMPLE_VK_FILE,
+    };
+    use ark_bn254::Fr;
+    use ark_ff::One;
+
+    #[test]
+    fn test_json_errors() {
+        let reason = |repr: &str| {
+            Fr::from_json(&repr.to_string())
+                .unwrap_err()
+                .downcast_ref::<VerifyError>()
+                .copied()
+        };
+        assert_eq!(reason("12a"), Some(VerifyError::InvalidFieldElement));
+        assert_eq!(
+            reason("0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001"),
+            Some(VerifyError::NonCanonicalFieldElement)
+        );
+
+        // Decimal strings are digits only, compared numerically with the
+        // field order
+        for repr in ["", "-1", "+5", "1_0", " 1", "1.0"] {
+            assert_eq!(
+                reason(repr),
+                Some(VerifyError::InvalidFieldElement),
+                "{repr}"
+            );
+        }
+        assert_eq!(Fr::from_json(&"007".to_string()).unwrap(), Fr::from(7u64));
+        assert_eq!(Fr::from_json(&"000".to_string()).unwrap(), Fr::from(0u64));
+        let r_minus_1 =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+        assert_eq!(Fr::from_json(&r_minus_1.to_string()).unwrap(), -Fr::one());
+        assert_eq!(
+            Fr::from_json(&format!("00{r_minus_1}")).unwrap(),
+            -Fr::one()
+        );
+        let two_256 =
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert_eq!(reason(two_256), Some(VerifyError::NonCanonicalFieldElement));
+
+        // The field order itself is not a canonical decimal field element
+        let r = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        assert_eq!(reason(r), Some(VerifyError::NonCanonicalFieldElement));
+
+        // Points are checked to be on the curve
+        let proof = sample().proof;
+        let off_curve = G1Affine::new_unchecked(proof.pi_a.x, proof.pi_a.x);
+        let err = G1Affine::from_json(&off_curve.to_json()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<VerifyError>(),
+            Some(&VerifyError::PointNotOnCurve)
+        );
+    }
+
+    #[test]
+    fn test_identity_points() {
+        let proof = sample().proof;
+
+        let g1 = G1Affine::identity();
+        let g2 = G2Affine::identity();
+        assert_eq!(G1Affine::from_json(&g1.to_json()).unwrap(), g1);
+        assert_eq!(G2Affine::from_json(&g2.to_json()).unwrap(), g2);
+        assert_eq!(g1.to_json(), vec!["0", "0"]);
+
+        // snarkjs projective coordinates
+        let json = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(G1Affine::from_json(&json(&["0", "1", "0"])).unwrap(), g1);
+        let g2_json = vec![
+            ["0".to_string(), "0".to_string()],
+            ["1".to_string(), "0".to_string()],
+            ["0".to_string(), "0".to_string()],
+        ];
+        assert_eq!(G2Affine::from_json(&g2_json).unwrap(), g2);
+        let mut pi_a = proof.pi_a.to_json();
+        pi_a.push("1".to_string());
+        assert_eq!(G1Affine::from_json(&pi_a).unwrap(), proof.pi_a);
+        let mut pi_b = proof.pi_b.to_json();
+        pi_b.push(["1".to_string(), "0".to_string()]);
+        let snarkjs_proof = ProofJson {
+            pi_a: pi_a.clone(),
+            pi_b,
+            pi_c: proof.pi_c.to_json(),
+        };
+        assert_eq!(Proof::from_json(&snarkjs_proof).unwrap(), proof);
+        pi_a[2] = "2".to_string();
+        assert!(G1Affine::from_json(&pi_a).is_err());
+        pi_a.push("1".to_string());
+        assert!(G1Affine::from_json(&pi_a).is_err());
+
+        // At infinity, the coordinates must be valid and equal to [0, 1]
+        for repr in [["0", "0", "0"], ["1", "1", "0"], ["0", "x", "0"]] {
+            assert!(G1Affine::from_json(&json(&repr)).is_err(), "{repr:?}");
+        }
+        let mut g2_json = g2_json;
+        g2_json[1][1] = "1".to_string();
+        assert!(G2Affine::from_json(&g2_json).is_err());
+
+        // Errors give the component of an Fp2 coordinate
+        let mut pi_b = proof.pi_b.to_json();
+        pi_b[1][1] = "x".to_string();
+        let err = G2Affine::from_json(&pi_b).unwrap_err();
+        assert_eq!(format!("{err:#}").split(':').next(), Some("c1"), "{err:#}");
+    }
+
+    #[test]
+    fn test_json_loading() {
+        let proof = sample().proof;
+        let bytes = std::fs::read(SAMPLE_PROOF_FILE).unwrap();
+        assert_eq!(parse_json::<Proof>(&bytes).unwrap(), proof);
+        assert_eq!(read_json::<Proof, _>(bytes.as_slice()).unwrap(), proof);
+
+        // Errors give the path, and the field for invalid values
+        let err = try_load_json::<Proof>("src/data/missing.json").unwrap_err();
+        assert!(
+            format!("{err:#}").contains("src/data/missing.json"),
+            "{err:#}"
+        );
+        assert!(parse_json::<Proof>(b"{").is_err());
+
+        let mut vk_json = load_json::<VerifyingKey>(SAMPLE_VK_FILE).to_json();
+        vk_json.s[1].swap(0, 1);
+        let err = parse_json::<VerifyingKey>(&serde_json::to_vec(&vk_json).unwrap()).unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(msg.starts_with("s: [1]: G1 point"), "{msg}");
+        assert_eq!(
+            err.downcast_ref::<VerifyError>(),
+            Some(&VerifyError::PointNotOnCurve)
+        );
+
+        // Invalid, empty or overlong hex is an error rather than a panic
+        for repr in ["0xzz", "0x", &format!("0x{}", "11".repeat(33))] {
+            let err = Fr::from_json(&repr.to_string()).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<VerifyError>(),
+                Some(&VerifyError::InvalidFieldElement)
+            );
+        }
+
+        // JSON Lines errors give the line number
+        let mut lines = std::fs::read_to_string(SAMPLE_APPLICATION_PROOFS_FILE).unwrap();
+        lines.push_str("{}\n");
+        let path = std::env::temp_dir().join("groth16_bad_application_proofs.jsonl");
+        std::fs::write(&path, lines).unwrap();
+        let err = try_read_jsonl::<ApplicationProof>(path.to_str().unwrap()).unwrap_err();
+        assert!(format!("{err:#}").contains("line 3"), "{err:#}");
+    }
+}
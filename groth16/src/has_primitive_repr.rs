@@ -1,16 +1,27 @@
-use crate::{Proof, VerifyingKey};
+use crate::batch::{Batch, BatchEntry};
+use crate::error::VerifyError;
+use crate::validate::fp_from_bigint;
+use crate::{Inputs, Proof, VerifyingKey};
 use ark_bn254::{G1Affine, G2Affine};
 use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
-use ark_ff::{BigInt, Fp, Fp2, Fp2Config, FpConfig, PrimeField};
+use ark_ff::{BigInt, Field, Fp, Fp2, Fp2Config, FpConfig, PrimeField, Zero};
 
 /// An object which has a representation in terms of primitive objects (which
 /// can be efficiently serialized between host and guest).
-pub trait HasPrimitiveRepr {
+pub trait HasPrimitiveRepr: Sized {
     type Repr;
 
     fn to_repr(&self) -> Self::Repr;
 
-    fn from_repr(repr: &Self::Repr) -> Self;
+    /// Convert from the representation, failing if it is invalid (e.g. a
+    /// field element which is not reduced). Curve points are not checked, see
+    /// `Proof::check` and `VerifyingKey::check`.
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError>;
+
+    /// Convert from a representation which is known to be valid.
+    fn from_repr(repr: &Self::Repr) -> Self {
+        Self::try_from_repr(repr).unwrap_or_else(|e| panic!("invalid repr: {e}"))
+    }
 }
 
 impl HasPrimitiveRepr for u32 {
@@ -18,8 +29,8 @@ impl HasPrimitiveRepr for u32 {
     fn to_repr(&self) -> Self {
         *self
     }
-    fn from_repr(repr: &Self) -> Self {
-        *repr
+    fn try_from_repr(repr: &Self) -> Result<Self, VerifyError> {
+        Ok(*repr)
     }
 }
 
@@ -28,8 +39,8 @@ impl HasPrimitiveRepr for u8 {
     fn to_repr(&self) -> Self {
         *self
     }
-    fn from_repr(repr: &Self) -> Self {
-        *repr
+    fn try_from_repr(repr: &Self) -> Result<Self, VerifyError> {
+        Ok(*repr)
     }
 }
 
@@ -42,8 +53,8 @@ impl<P: FpConfig<N>, const N: usize> HasPrimitiveRepr for Fp<P, N> {
         self.into_bigint().0
     }
 
-    fn from_repr(repr: &Self::Repr) -> Self {
-        Self::from_bigint(BigInt(*repr)).unwrap()
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        fp_from_bigint(BigInt(*repr))
     }
 }
 
@@ -57,11 +68,11 @@ where
         [self.c0.to_repr(), self.c1.to_repr()]
     }
 
-    fn from_repr(repr: &Self::Repr) -> Self {
-        Self {
-            c0: P::Fp::from_repr(&repr[0]),
-            c1: P::Fp::from_repr(&repr[1]),
-        }
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        Ok(Self {
+            c0: P::Fp::try_from_repr(&repr[0])?,
+            c1: P::Fp::try_from_repr(&repr[1])?,
+        })
     }
 }
 
@@ -71,17 +82,22 @@ where
 {
     type Repr = [<P::BaseField as HasPrimitiveRepr>::Repr; 2];
 
+    /// The point at infinity is represented by the coordinates (0, 0), which
+    /// are not on the curve.
     fn to_repr(&self) -> Self::Repr {
-        assert!(!self.infinity);
+        if self.infinity {
+            return [P::BaseField::ZERO.to_repr(), P::BaseField::ZERO.to_repr()];
+        }
         [self.x.to_repr(), self.y.to_repr()]
     }
 
-    fn from_repr(repr: &Self::Repr) -> Self {
-        Self {
-            x: P::BaseField::from_repr(&repr[0]),
-            y: P::BaseField::from_repr(&repr[1]),
-            infinity: false,
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        let x = P::BaseField::try_from_repr(&repr[0])?;
+        let y = P::BaseField::try_from_repr(&repr[1])?;
+        if x.is_zero() && y.is_zero() {
+            return Ok(Self::identity());
         }
+        Ok(Self::new_unchecked(x, y))
     }
 }
 
@@ -95,12 +111,12 @@ where
         [self.x.to_repr(), self.y.to_repr(), self.z.to_repr()]
     }
 
-    fn from_repr(repr: &Self::Repr) -> Self {
-        Self {
-            x: P::BaseField::from_repr(&repr[0]),
-            y: P::BaseField::from_repr(&repr[1]),
-            z: P::BaseField::from_repr(&repr[2]),
-        }
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        Ok(Self {
+            x: P::BaseField::try_from_repr(&repr[0])?,
+            y: P::BaseField::try_from_repr(&repr[1])?,
+            z: P::BaseField::try_from_repr(&repr[2])?,
+        })
     }
 }
 
@@ -111,8 +127,8 @@ impl<T: HasPrimitiveRepr> HasPrimitiveRepr for Vec<T> {
         self.iter().map(T::to_repr).collect()
     }
 
-    fn from_repr(repr: &Self::Repr) -> Self {
-        repr.iter().map(T::from_repr).collect()
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        repr.iter().map(T::try_from_repr).collect()
     }
 }
 
@@ -123,8 +139,12 @@ impl<T: HasPrimitiveRepr, const N: usize> HasPrimitiveRepr for [T; N] {
         self.each_ref().map(T::to_repr)
     }
 
-    fn from_repr(repr: &Self::Repr) -> Self {
-        repr.each_ref().map(T::from_repr)
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        let values = repr
+            .iter()
+            .map(T::try_from_repr)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
     }
 }
 
@@ -141,12 +161,12 @@ impl HasPrimitiveRepr for Proof {
             self.pi_c.to_repr(),
         )
     }
-    fn from_repr(repr: &Self::Repr) -> Self {
-        Self {
-            pi_a: <G1Affine as HasPrimitiveRepr>::from_repr(&repr.0),
-            pi_b: <G2Affine as HasPrimitiveRepr>::from_repr(&repr.1),
-            pi_c: <G1Affine as HasPrimitiveRepr>::from_repr(&repr.2),
-        }
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        Ok(Self {
+            pi_a: <G1Affine as HasPrimitiveRepr>::try_from_repr(&repr.0)?,
+            pi_b: <G2Affine as HasPrimitiveRepr>::try_from_repr(&repr.1)?,
+            pi_c: <G1Affine as HasPrimitiveRepr>::try_from_repr(&repr.2)?,
+        })
     }
 }
 
@@ -167,13 +187,91 @@ impl HasPrimitiveRepr for VerifyingKey {
             self.s.to_repr(),
         )
     }
-    fn from_repr(repr: &Self::Repr) -> Self {
-        Self {
-            alpha: <G1Affine as HasPrimitiveRepr>::from_repr(&repr.0),
-            beta: <G2Affine as HasPrimitiveRepr>::from_repr(&repr.1),
-            gamma: <G2Affine as HasPrimitiveRepr>::from_repr(&repr.2),
-            delta: <G2Affine as HasPrimitiveRepr>::from_repr(&repr.3),
-            s: <Vec<G1Affine> as HasPrimitiveRepr>::from_repr(&repr.4),
-        }
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        Ok(Self {
+            alpha: <G1Affine as HasPrimitiveRepr>::try_from_repr(&repr.0)?,
+            beta: <G2Affine as HasPrimitiveRepr>::try_from_repr(&repr.1)?,
+            gamma: <G2Affine as HasPrimitiveRepr>::try_from_repr(&repr.2)?,
+            delta: <G2Affine as HasPrimitiveRepr>::try_from_repr(&repr.3)?,
+            s: <Vec<G1Affine> as HasPrimitiveRepr>::try_from_repr(&repr.4)?,
+        })
+    }
+}
+
+impl HasPrimitiveRepr for BatchEntry {
+    type Repr = (
+        u32,
+        <Inputs as HasPrimitiveRepr>::Repr,
+        <Proof as HasPrimitiveRepr>::Repr,
+    );
+    fn to_repr(&self) -> Self::Repr {
+        (self.vk_index, self.inputs.to_repr(), self.proof.to_repr())
+    }
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        Ok(Self {
+            vk_index: repr.0,
+            inputs: <Inputs as HasPrimitiveRepr>::try_from_repr(&repr.1)?,
+            proof: <Proof as HasPrimitiveRepr>::try_from_repr(&repr.2)?,
+        })
+    }
+}
+
+impl HasPrimitiveRepr for Batch {
+    type Repr = (
+        <Vec<VerifyingKey> as HasPrimitiveRepr>::Repr,
+        <Vec<BatchEntry> as HasPrimitiveRepr>::Repr,
+    );
+    fn to_repr(&self) -> Self::Repr {
+        (self.vks.to_repr(), self.entries.to_repr())
+    }
+    fn try_from_repr(repr: &Self::Repr) -> Result<Self, VerifyError> {
+        Ok(Self {
+            vks: <Vec<VerifyingKey> as HasPrimitiveRepr>::try_from_repr(&repr.0)?,
+            entries: <Vec<BatchEntry> as HasPrimitiveRepr>::try_from_repr(&repr.1)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sample::sample_batch;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_identity_points() {
+        let g1 = G1Affine::identity();
+        let g2 = G2Affine::identity();
+        assert_eq!(G1Affine::from_repr(&g1.to_repr()), g1);
+        assert_eq!(G2Affine::from_repr(&g2.to_repr()), g2);
+        assert_eq!(G1Affine::try_from_repr(&g1.to_repr()), Ok(g1));
+        assert_eq!(G2Affine::try_from_repr(&g2.to_repr()), Ok(g2));
+    }
+
+    #[test]
+    fn test_try_from_repr() {
+        let batch = sample_batch(2);
+        assert_eq!(Batch::try_from_repr(&batch.to_repr()), Ok(batch.clone()));
+
+        // The field order, as limbs, is not a reduced field element
+        let modulus = <Fr as PrimeField>::MODULUS.0;
+        assert_eq!(
+            Fr::try_from_repr(&modulus),
+            Err(VerifyError::NonCanonicalFieldElement)
+        );
+
+        // A non-canonical input or coordinate in one entry
+        let mut entry_repr = batch.entries[1].to_repr();
+        entry_repr.1[0] = modulus;
+        assert_eq!(
+            BatchEntry::try_from_repr(&entry_repr),
+            Err(VerifyError::NonCanonicalFieldElement)
+        );
+        let mut batch_repr = batch.to_repr();
+        batch_repr.1[1].2 .1[0][1] = [u64::MAX; 4];
+        assert_eq!(
+            Batch::try_from_repr(&batch_repr),
+            Err(VerifyError::NonCanonicalFieldElement)
+        );
     }
 }
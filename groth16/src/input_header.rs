@@ -0,0 +1,144 @@
+use crate::batch::{Batch, BatchEntry};
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Marks the start of the guest input ("G16A")
+pub const INPUT_MAGIC: u32 = u32::from_be_bytes(*b"G16A");
+
+/// Version of the guest input layout. Increment this whenever the layout
+/// following the header changes.
+pub const INPUT_VERSION: u32 = 1;
+
+/// The proofs follow the verification keys one at a time, rather than as a
+/// single `Batch`.
+pub const FLAG_STREAMING: u32 = 1 << 0;
+
+/// Header written by the host before the guest input, so that a guest built
+/// for a different input layout fails with a clear error rather than while
+/// deserializing the rest of the input.
+///
+/// It is followed either by the `Batch` repr or, in streaming mode, by the
+/// repr of the `num_vks` verification keys and then the repr of each of the
+/// `num_proofs` batch entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputHeader {
+    pub magic: u32,
+    pub version: u32,
+    pub flags: u32,
+    pub num_vks: u32,
+    pub num_proofs: u32,
+}
+
+impl InputHeader {
+    /// The header for sending `batch` to the guest.
+    pub fn new(batch: &Batch, streaming: bool) -> Self {
+        Self {
+            magic: INPUT_MAGIC,
+            version: INPUT_VERSION,
+            flags: if streaming { FLAG_STREAMING } else { 0 },
+            num_vks: batch.vks.len() as u32,
+            num_proofs: batch.len() as u32,
+        }
+    }
+
+    pub fn streaming(&self) -> bool {
+        self.flags & FLAG_STREAMING != 0
+    }
+
+    /// Check that the header was written for this version of the input
+    /// layout.
+    pub fn check(&self) -> Result<()> {
+        if self.magic != INPUT_MAGIC {
+            return Err(Error::msg(format!(
+                "bad input magic {:#010x} (expected {INPUT_MAGIC:#010x}): \
+                 the host is not writing a versioned input header",
+                self.magic
+            )));
+        }
+        if self.version != INPUT_VERSION {
+            return Err(Error::msg(format!(
+                "input version {} is not supported (expected {INPUT_VERSION}): \
+                 the host and guest were built from different versions",
+                self.version
+            )));
+        }
+        if self.flags & !FLAG_STREAMING != 0 {
+            return Err(Error::msg(format!("unknown input flags {:#x}", self.flags)));
+        }
+        Ok(())
+    }
+
+    /// Check that `batch`, read after the header, has the expected number of
+    /// verification keys and proofs, and that each proof refers to one of the
+    /// keys.
+    pub fn check_batch(&self, batch: &Batch) -> Result<()> {
+        if batch.vks.len() != self.num_vks as usize || batch.len() != self.num_proofs as usize {
+            return Err(Error::msg(format!(
+                "batch has {} verification keys and {} proofs (expected {} and {})",
+                batch.vks.len(),
+                batch.len(),
+                self.num_vks,
+                self.num_proofs
+            )));
+        }
+        for (i, entry) in batch.entries.iter().enumerate() {
+            self.check_entry(entry)
+                .map_err(|e| Error::msg(format!("proof {i}: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// Check that `entry` refers to one of the `num_vks` verification keys.
+    pub fn check_entry(&self, entry: &BatchEntry) -> Result<()> {
+        if entry.vk_index >= self.num_vks {
+            return Err(Error::msg(format!(
+                "verification key index {} out of range ({} keys)",
+                entry.vk_index, self.num_vks
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sample::sample_batch;
+
+    #[test]
+    fn test_input_header() {
+        let batch = sample_batch(3);
+
+        let header = InputHeader::new(&batch, true);
+        assert!(header.streaming());
+        assert!(header.check().is_ok());
+        assert!(header.check_batch(&batch).is_ok());
+        assert!(!InputHeader::new(&batch, false).streaming());
+
+        // Wrong magic, version or flags
+        let bad_magic = InputHeader { magic: 0, ..header };
+        assert!(bad_magic.check().is_err());
+        let bad_version = InputHeader {
+            version: INPUT_VERSION + 1,
+            ..header
+        };
+        assert!(bad_version.check().is_err());
+        let bad_flags = InputHeader { flags: 2, ..header };
+        assert!(bad_flags.check().is_err());
+
+        // Wrong number of proofs
+        let bad_count = InputHeader {
+            num_proofs: 2,
+            ..header
+        };
+        assert!(bad_count.check_batch(&batch).is_err());
+
+        // Proof referring to a missing verification key
+        let mut bad_index = batch.clone();
+        bad_index.entries[1].vk_index = 1;
+        let err = header.check_batch(&bad_index).unwrap_err();
+        assert!(err.to_string().starts_with("proof 1: "), "{err}");
+        assert!(header.check_entry(&bad_index.entries[0]).is_ok());
+        assert!(header.check_entry(&bad_index.entries[1]).is_err());
+    }
+}
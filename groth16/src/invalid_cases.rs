@@ -0,0 +1,169 @@
+//! A corpus of invalid proofs, each with the error it must be rejected with.
+//!
+//! The cases in `data/invalid_cases.jsonl` are variations of the sample proof
+//! in `data`: tampered proofs and keys, points off the curve or outside the
+//! subgroup, non-canonical field elements, wrong numbers of inputs and
+//! identity points. They are kept in their JSON representation, since many of
+//! them cannot be parsed, and are checked both by the native verifier (see
+//! `InvalidCase::verify`) and by the guests (see `InvalidCase::guest_input`).
+
+use crate::batch::Batch;
+use crate::error::VerifyError;
+use crate::has_json_repr::{
+    bigint_from_decimal, le_bytes32_from_hex, G1AffineJson, G2AffineJson, HasJsonRepr, ProofJson,
+    VerifyingKeyJson,
+};
+use crate::has_primitive_repr::HasPrimitiveRepr;
+use crate::input_header::{InputHeader, INPUT_MAGIC, INPUT_VERSION};
+use crate::{verify_checked, Inputs, Proof, VerifyingKey};
+use anyhow::{Context, Error, Result};
+use ark_bn254::{G1Affine, G2Affine};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+
+/// A verifying key, proof and inputs which must be rejected with `expected`.
+/// Load the cases with `load_invalid_cases`.
+#[derive(Serialize, Deserialize)]
+pub struct InvalidCase {
+    pub name: String,
+    pub description: String,
+    pub vk: VerifyingKeyJson,
+    pub proof: ProofJson,
+    pub inputs: Vec<String>,
+    pub expected: VerifyError,
+}
+
+type G1Repr = <G1Affine as HasPrimitiveRepr>::Repr;
+type G2Repr = <G2Affine as HasPrimitiveRepr>::Repr;
+
+impl InvalidCase {
+    /// Load and verify the case as the host does: each value is checked as it
+    /// is parsed, and the proof is verified with `verify_checked`. The
+    /// `VerifyError` can be recovered from the error with `downcast_ref`.
+    pub fn verify(&self) -> Result<()> {
+        let vk = VerifyingKey::from_json(&self.vk).context("vk")?;
+        let proof = Proof::from_json(&self.proof).context("proof")?;
+        let inputs = Inputs::from_json(&self.inputs).context("inputs")?;
+        verify_checked(&vk, &proof, &inputs).map_err(Error::msg)
+    }
+
+    /// The guest input for a batch of this single proof: the header and the
+    /// `Batch` repr. The values are converted without any checks, so that the
+    /// guest receives the invalid values themselves.
+    pub fn guest_input(&self) -> Result<(InputHeader, <Batch as HasPrimitiveRepr>::Repr)> {
+        let vk = (
+            g1_repr(&self.vk.alpha).context("vk: alpha")?,
+            g2_repr(&self.vk.beta).context("vk: beta")?,
+            g2_repr(&self.vk.gamma).context("vk: gamma")?,
+            g2_repr(&self.vk.delta).context("vk: delta")?,
+            self.vk
+                .s
+                .iter()
+                .map(g1_repr)
+                .collect::<Result<Vec<_>>>()
+                .context("vk: s")?,
+        );
+        let proof = (
+            g1_repr(&self.proof.pi_a).context("proof: pi_a")?,
+            g2_repr(&self.proof.pi_b).context("proof: pi_b")?,
+            g1_repr(&self.proof.pi_c).context("proof: pi_c")?,
+        );
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| limbs_from_json(input))
+            .collect::<Result<Vec<_>>>()
+            .context("inputs")?;
+        let header = InputHeader {
+            magic: INPUT_MAGIC,
+            version: INPUT_VERSION,
+            flags: 0,
+            num_vks: 1,
+            num_proofs: 1,
+        };
+        Ok((header, (vec![vk], vec![(0, inputs, proof)])))
+    }
+}
+
+/// The corpus of invalid cases
+pub const INVALID_CASES_FILE: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/src/data/invalid_cases.jsonl");
+
+/// Load the cases from a JSON Lines file, panicking on error.
+pub fn load_invalid_cases(path: &str) -> Vec<InvalidCase> {
+    let file = File::open(path).unwrap_or_else(|e| panic!("failed to open {path}: {e}"));
+    serde_json::Deserializer::from_reader(BufReader::new(file))
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| panic!("failed to load {path}: {e}"))
+}
+
+/// Little-endian limbs of a (decimal or "0x"-prefixed hex) integer of at most
+/// 256 bits, which is not reduced modulo the field order.
+fn limbs_from_json(repr: &str) -> Result<[u64; 4]> {
+    if repr.starts_with("0x") {
+        let bytes = le_bytes32_from_hex(repr).map_err(Error::msg)?;
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        return Ok(limbs);
+    }
+    let bigint = bigint_from_decimal(repr).map_err(|e| Error::msg(format!("{e}: {repr}")))?;
+    Ok(bigint.0)
+}
+
+fn xy_json<T>(repr: &[T]) -> Result<(&T, &T)> {
+    match repr {
+        [x, y] => Ok((x, y)),
+        _ => Err(Error::msg(format!(
+            "points must have 2 coordinates, got {}",
+            repr.len()
+        ))),
+    }
+}
+
+fn g1_repr(repr: &G1AffineJson) -> Result<G1Repr> {
+    let (x, y) = xy_json(repr)?;
+    Ok([limbs_from_json(x)?, limbs_from_json(y)?])
+}
+
+fn g2_repr(repr: &G2AffineJson) -> Result<G2Repr> {
+    let (x, y) = xy_json(repr)?;
+    Ok([
+        [limbs_from_json(&x[0])?, limbs_from_json(&x[1])?],
+        [limbs_from_json(&y[0])?, limbs_from_json(&y[1])?],
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_invalid_cases() {
+        let cases = load_invalid_cases(INVALID_CASES_FILE);
+        assert!(!cases.is_empty());
+        for case in &cases {
+            // Rejected with the expected reason when loaded by the host
+            let err = case.verify().unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<VerifyError>(),
+                Some(&case.expected),
+                "{}: {err:#}",
+                case.name
+            );
+
+            // and by the checks the guest makes on its input
+            let (header, batch_repr) = case.guest_input().unwrap();
+            let result = Batch::try_from_repr(&batch_repr).and_then(|batch| {
+                assert!(header.check_batch(&batch).is_ok());
+                batch.vks[0].check()?;
+                let entry = &batch.entries[0];
+                verify_checked(&batch.vks[0], &entry.proof, &entry.inputs)
+            });
+            assert_eq!(result, Err(case.expected), "{}", case.name);
+        }
+    }
+}
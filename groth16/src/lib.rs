@@ -1,15 +1,25 @@
-use anyhow::{Error, Result};
+use crate::error::VerifyError;
 use ark_bn254::{Bn254, Fq12, Fr, G1Affine, G1Projective, G2Affine};
 use ark_ec::pairing::Pairing;
 use ark_ff::Field;
 
+pub mod batch;
+pub mod cycle_baseline;
+pub mod error;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 pub mod has_json_repr;
 pub mod has_primitive_repr;
+pub mod input_header;
+pub mod invalid_cases;
+pub mod rerandomize;
+pub mod sample;
+pub mod validate;
 
 /// Public inputs
 pub type Inputs = Vec<Fr>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct VerifyingKey {
     pub alpha: G1Affine,
     pub beta: G2Affine,
@@ -18,15 +28,89 @@ pub struct VerifyingKey {
     pub s: Vec<G1Affine>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Proof {
     pub pi_a: G1Affine,
     pub pi_b: G2Affine,
     pub pi_c: G1Affine,
 }
 
-/// Groth16 verifier
-pub fn verify(vk: &VerifyingKey, proof: &Proof, inputs: &Inputs) -> Result<()> {
+impl VerifyingKey {
+    /// A verifying key from its components, checking that each point is valid.
+    pub fn new(
+        alpha: G1Affine,
+        beta: G2Affine,
+        gamma: G2Affine,
+        delta: G2Affine,
+        s: Vec<G1Affine>,
+    ) -> Result<Self, VerifyError> {
+        let vk = Self::new_unchecked(alpha, beta, gamma, delta, s);
+        vk.check()?;
+        Ok(vk)
+    }
+
+    /// A verifying key from its components, assumed to be valid.
+    pub fn new_unchecked(
+        alpha: G1Affine,
+        beta: G2Affine,
+        gamma: G2Affine,
+        delta: G2Affine,
+        s: Vec<G1Affine>,
+    ) -> Self {
+        Self {
+            alpha,
+            beta,
+            gamma,
+            delta,
+            s,
+        }
+    }
+
+    /// Check that all points are on the curve and in the prime-order
+    /// subgroup, and that there is at least one input commitment.
+    pub fn check(&self) -> Result<(), VerifyError> {
+        if self.s.is_empty() {
+            return Err(VerifyError::InvalidVerifyingKey);
+        }
+        validate::check_g1(&self.alpha)?;
+        validate::check_g2(&self.beta)?;
+        validate::check_g2(&self.gamma)?;
+        validate::check_g2(&self.delta)?;
+        self.s.iter().try_for_each(validate::check_g1)
+    }
+
+    /// Number of public inputs expected by the key
+    pub fn num_inputs(&self) -> usize {
+        self.s.len().saturating_sub(1)
+    }
+}
+
+impl Proof {
+    /// A proof from its components, checking that each point is valid.
+    pub fn new(pi_a: G1Affine, pi_b: G2Affine, pi_c: G1Affine) -> Result<Self, VerifyError> {
+        let proof = Self::new_unchecked(pi_a, pi_b, pi_c);
+        proof.check()?;
+        Ok(proof)
+    }
+
+    /// A proof from its components, assumed to be valid.
+    pub fn new_unchecked(pi_a: G1Affine, pi_b: G2Affine, pi_c: G1Affine) -> Self {
+        Self { pi_a, pi_b, pi_c }
+    }
+
+    /// Check that all points are on the curve and in the prime-order
+    /// subgroup.
+    pub fn check(&self) -> Result<(), VerifyError> {
+        validate::check_g1(&self.pi_a)?;
+        validate::check_g2(&self.pi_b)?;
+        validate::check_g1(&self.pi_c)
+    }
+}
+
+/// Groth16 verifier. The points of `vk` and `proof` are assumed to be valid
+/// (see `VerifyingKey::check` and `Proof::check`). The number of inputs is
+/// checked.
+pub fn verify(vk: &VerifyingKey, proof: &Proof, inputs: &Inputs) -> Result<(), VerifyError> {
     // Check:
     //   e(-pf.a, pf.b)
     //   e(vk.alpha, vk.beta)
@@ -37,11 +121,20 @@ pub fn verify(vk: &VerifyingKey, proof: &Proof, inputs: &Inputs) -> Result<()> {
     // where:
     //   p = vk.s[0] + \sum_i=1^\ell input[i] * vk.s[i]
 
+    if vk.s.is_empty() {
+        return Err(VerifyError::InvalidVerifyingKey);
+    }
+    if inputs.len() != vk.num_inputs() {
+        return Err(VerifyError::InputLengthMismatch {
+            expected: vk.num_inputs(),
+            actual: inputs.len(),
+        });
+    }
+
     // Naive computation of P
-    let num_inputs = inputs.len();
     let mut p: G1Projective = vk.s[0].into();
-    for (i, input) in inputs.iter().enumerate().take(num_inputs) {
-        p += vk.s[i + 1] * input
+    for (s, input) in vk.s[1..].iter().zip(inputs) {
+        p += *s * input
     }
 
     let miller_out = Bn254::multi_miller_loop(
@@ -53,29 +146,67 @@ pub fn verify(vk: &VerifyingKey, proof: &Proof, inputs: &Inputs) -> Result<()> {
         if result.0 == Fq12::ONE {
             Ok(())
         } else {
-            Err(Error::msg("pairing result"))
+            Err(VerifyError::PairingMismatch)
         }
     } else {
-        Err(Error::msg("pairing failed"))
+        Err(VerifyError::PairingFailed)
     }
 }
 
+/// As `verify`, but first check that the points of `proof` are valid, for
+/// proofs from an untrusted source.
+pub fn verify_checked(
+    vk: &VerifyingKey,
+    proof: &Proof,
+    inputs: &Inputs,
+) -> Result<(), VerifyError> {
+    proof.check()?;
+    verify(vk, proof, inputs)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::has_json_repr::load_json;
+    use crate::batch::Batch;
+    use crate::has_json_repr::HasJsonRepr;
+    use crate::has_primitive_repr::HasPrimitiveRepr;
+    use crate::sample::{sample, Sample};
     use ark_ff::One;
 
     #[test]
     fn test_groth16() {
-        let proof: Proof = load_json("src/data/proof.json");
-        let vk: VerifyingKey = load_json("src/data/vk.json");
-        let mut inputs: Inputs = load_json("src/data/inputs.json");
+        let Sample {
+            vk,
+            proof,
+            mut inputs,
+        } = sample();
 
         assert!(verify(&vk, &proof, &inputs).is_ok());
 
         // Failure case
         inputs[0] = Fr::one();
-        assert!(verify(&vk, &proof, &inputs).is_err())
+        assert_eq!(
+            verify(&vk, &proof, &inputs),
+            Err(VerifyError::PairingMismatch)
+        );
+    }
+
+    #[test]
+    fn test_identity_input_commitment() {
+        let Sample {
+            mut vk,
+            proof,
+            mut inputs,
+        } = sample();
+
+        // A key with an identity input commitment accepts any value for that
+        // input.
+        vk.s.push(G1Affine::identity());
+        inputs.push(Fr::from(42u64));
+        assert!(vk.check().is_ok());
+        assert!(verify(&vk, &proof, &inputs).is_ok());
+        assert_eq!(VerifyingKey::from_json(&vk.to_json()).unwrap(), vk);
+        let batch = Batch::repeated(vk, inputs, proof, 2);
+        assert_eq!(Batch::from_repr(&batch.to_repr()), batch);
     }
 }
@@ -0,0 +1,42 @@
+use crate::{Proof, VerifyingKey};
+use ark_bn254::{Fr, G1Projective, G2Projective};
+use ark_ff::Field;
+
+/// Re-randomise a valid proof for `vk`, giving a different proof which is
+/// also valid for the same public inputs, or `None` if `r1` is zero. With
+/// scalars `r1 != 0` and `r2`:
+///
+///   A' = r1^{-1} A
+///   B' = r1 B + r1 r2 delta
+///   C' = C + r2 A
+///
+/// so that e(A', B') = e(A, B) e(r2 A, delta), and the extra factor is
+/// matched by e(C', delta) = e(C, delta) e(r2 A, delta).
+pub fn rerandomize(vk: &VerifyingKey, proof: &Proof, r1: Fr, r2: Fr) -> Option<Proof> {
+    let r1_inv = r1.inverse()?;
+    let pi_a: G1Projective = proof.pi_a * r1_inv;
+    let pi_b: G2Projective = proof.pi_b * r1 + vk.delta * (r1 * r2);
+    let pi_c: G1Projective = proof.pi_c + proof.pi_a * r2;
+    Some(Proof {
+        pi_a: pi_a.into(),
+        pi_b: pi_b.into(),
+        pi_c: pi_c.into(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sample::{sample, Sample};
+    use crate::verify;
+
+    #[test]
+    fn test_rerandomize() {
+        let Sample { vk, proof, inputs } = sample();
+
+        let rerandomized = rerandomize(&vk, &proof, Fr::from(3u64), Fr::from(5u64)).unwrap();
+        assert_ne!(rerandomized, proof);
+        assert!(verify(&vk, &rerandomized, &inputs).is_ok());
+        assert_eq!(rerandomize(&vk, &proof, Fr::ZERO, Fr::from(5u64)), None);
+    }
+}
@@ -0,0 +1,43 @@
+//! The sample application proof in `data`, which the hosts verify by default
+//! and the tests use.
+
+use crate::batch::Batch;
+use crate::has_json_repr::load_json;
+use crate::{Inputs, Proof, VerifyingKey};
+
+/// Verification key of the sample proof
+pub const SAMPLE_VK_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/data/vk.json");
+/// The sample proof
+pub const SAMPLE_PROOF_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/data/proof.json");
+/// Public inputs of the sample proof
+pub const SAMPLE_INPUTS_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/data/inputs.json");
+/// Application proofs for `Batch::load`: two copies of the sample proof
+pub const SAMPLE_APPLICATION_PROOFS_FILE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/data/application_proofs.jsonl"
+);
+/// Verification key table of `SAMPLE_APPLICATION_PROOFS_FILE`
+pub const SAMPLE_VK_TABLE_FILE: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/src/data/vk_table.json");
+
+/// The sample proof with its verification key and public inputs.
+pub struct Sample {
+    pub vk: VerifyingKey,
+    pub proof: Proof,
+    pub inputs: Inputs,
+}
+
+/// Load the sample proof, panicking on error.
+pub fn sample() -> Sample {
+    Sample {
+        vk: load_json(SAMPLE_VK_FILE),
+        proof: load_json(SAMPLE_PROOF_FILE),
+        inputs: load_json(SAMPLE_INPUTS_FILE),
+    }
+}
+
+/// A batch of `batch_size` copies of the sample proof, panicking on error.
+pub fn sample_batch(batch_size: u32) -> Batch {
+    let Sample { vk, proof, inputs } = sample();
+    Batch::repeated(vk, inputs, proof, batch_size)
+}
@@ -0,0 +1,188 @@
+//! Validation of field elements and curve points received from untrusted
+//! sources.
+//!
+//! The checked constructors reject non-canonical field elements, points which
+//! are not on the curve and (for G2) points outside the prime-order subgroup.
+//! The `_unchecked` variants skip these checks, for data which has already
+//! been validated (e.g. by the host before it is sent to the guest).
+
+use crate::error::VerifyError;
+use ark_bn254::{Fq12Config, Fq2, Fq6Config, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInt, Field, Fp, Fp12Config, Fp6Config, FpConfig};
+
+/// BN254 curve parameter x
+const X: u128 = 4965661367192848881;
+
+/// 6 x^2, as little-endian limbs
+const SIX_X_SQUARED: [u64; 2] = {
+    let v = 6 * X * X;
+    [v as u64, (v >> 64) as u64]
+};
+
+/// Field element from its integer representation, which must be less than
+/// the modulus.
+pub fn fp_from_bigint<P: FpConfig<N>, const N: usize>(
+    b: BigInt<N>,
+) -> Result<Fp<P, N>, VerifyError> {
+    P::from_bigint(b).ok_or(VerifyError::NonCanonicalFieldElement)
+}
+
+/// Check that `p` is on the curve. G1 has cofactor 1, so this also implies
+/// that it is in the prime-order subgroup.
+pub fn check_g1(p: &G1Affine) -> Result<(), VerifyError> {
+    if !p.is_on_curve() {
+        return Err(VerifyError::PointNotOnCurve);
+    }
+    Ok(())
+}
+
+/// Check that `p` is on the twist and in the prime-order subgroup.
+pub fn check_g2(p: &G2Affine) -> Result<(), VerifyError> {
+    if !p.is_on_curve() {
+        return Err(VerifyError::PointNotOnCurve);
+    }
+    if !g2_in_subgroup_assuming_on_curve(p) {
+        return Err(VerifyError::PointNotInSubgroup);
+    }
+    Ok(())
+}
+
+/// Subgroup membership test of section 4.3 of
+/// https://eprint.iacr.org/2022/352.pdf: for BN curves, a point P on the
+/// twist is in G2 iff psi(P) = [6 x^2] P, where psi is the untwist-Frobenius-
+/// twist endomorphism. This is much cheaper than checking [r] P = 0.
+pub fn g2_in_subgroup_assuming_on_curve(p: &G2Affine) -> bool {
+    p.mul_bigint(SIX_X_SQUARED) == psi(p)
+}
+
+/// The untwist-Frobenius-twist endomorphism on G2:
+///
+///   psi(x, y) = (x^p xi^((p-1)/3), y^p xi^((p-1)/2))
+///
+/// where xi = 9 + u is the non-residue defining the twist.
+fn psi(p: &G2Affine) -> G2Affine {
+    if p.infinity {
+        return *p;
+    }
+    // xi^((p-1)/3) and xi^((p-1)/6), from the Frobenius coefficients of the
+    // tower.
+    let coeff_x: Fq2 = Fq6Config::FROBENIUS_COEFF_FP6_C1[1];
+    let coeff_y_6: Fq2 = Fq12Config::FROBENIUS_COEFF_FP12_C1[1];
+    let coeff_y = coeff_y_6 * coeff_y_6.square();
+
+    let mut x = p.x;
+    let mut y = p.y;
+    x.frobenius_map_in_place(1);
+    y.frobenius_map_in_place(1);
+    G2Affine::new_unchecked(x * coeff_x, y * coeff_y)
+}
+
+/// A G1 point with the given coordinates, which must be on the curve.
+pub fn g1_from_xy(x: ark_bn254::Fq, y: ark_bn254::Fq) -> Result<G1Affine, VerifyError> {
+    let p = g1_from_xy_unchecked(x, y);
+    check_g1(&p)?;
+    Ok(p)
+}
+
+/// A G1 point with the given coordinates, assumed to be valid.
+pub fn g1_from_xy_unchecked(x: ark_bn254::Fq, y: ark_bn254::Fq) -> G1Affine {
+    G1Affine::new_unchecked(x, y)
+}
+
+/// A G2 point with the given coordinates, which must be on the twist and in
+/// the prime-order subgroup.
+pub fn g2_from_xy(x: Fq2, y: Fq2) -> Result<G2Affine, VerifyError> {
+    let p = g2_from_xy_unchecked(x, y);
+    check_g2(&p)?;
+    Ok(p)
+}
+
+/// A G2 point with the given coordinates, assumed to be valid.
+pub fn g2_from_xy_unchecked(x: Fq2, y: Fq2) -> G2Affine {
+    G2Affine::new_unchecked(x, y)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sample::{sample, Sample};
+    use crate::{verify, verify_checked, Proof, VerifyingKey};
+    use ark_bn254::{Fq, Fr, G2Projective};
+    use ark_ec::CurveGroup;
+    use ark_ff::One;
+
+    #[test]
+    fn test_validation() {
+        let Sample { vk, proof, inputs } = sample();
+        assert!(proof.check().is_ok());
+        assert!(vk.check().is_ok());
+        assert!(verify_checked(&vk, &proof, &inputs).is_ok());
+
+        // The endomorphism subgroup check agrees with [r] P = 0, for points in
+        // the subgroup and (most) points on the twist outside it.
+        let g2 = G2Affine::generator();
+        for k in 1..8u64 {
+            let p = (G2Projective::from(g2) * Fr::from(k)).into_affine();
+            assert!(g2_in_subgroup_assuming_on_curve(&p));
+            assert!(check_g2(&p).is_ok());
+        }
+        let mut num_outside = 0;
+        for k in 1..16u64 {
+            let x = Fq2::new(Fq::from(k), Fq::one());
+            if let Some(p) = G2Affine::get_point_from_x_unchecked(x, false) {
+                assert!(p.is_on_curve());
+                let in_subgroup = p.is_in_correct_subgroup_assuming_on_curve();
+                assert_eq!(g2_in_subgroup_assuming_on_curve(&p), in_subgroup);
+                if !in_subgroup {
+                    assert_eq!(check_g2(&p), Err(VerifyError::PointNotInSubgroup));
+                    num_outside += 1;
+                }
+            }
+        }
+        assert!(num_outside > 0);
+
+        // The identity is valid in both groups
+        assert!(check_g1(&G1Affine::identity()).is_ok());
+        assert!(check_g2(&G2Affine::identity()).is_ok());
+
+        // Points off the curve
+        let off_curve = G1Affine::new_unchecked(proof.pi_a.x, proof.pi_a.x);
+        assert_eq!(check_g1(&off_curve), Err(VerifyError::PointNotOnCurve));
+        let off_twist = G2Affine::new_unchecked(proof.pi_b.x, proof.pi_b.x);
+        assert_eq!(check_g2(&off_twist), Err(VerifyError::PointNotOnCurve));
+        assert_eq!(
+            Proof::new(off_curve, proof.pi_b, proof.pi_c),
+            Err(VerifyError::PointNotOnCurve)
+        );
+        let bad_proof = Proof::new_unchecked(proof.pi_a, off_twist, proof.pi_c);
+        assert_eq!(
+            verify_checked(&vk, &bad_proof, &inputs),
+            Err(VerifyError::PointNotOnCurve)
+        );
+
+        // Wrong number of inputs
+        let mut too_many = inputs.clone();
+        too_many.push(Fr::one());
+        assert_eq!(
+            verify(&vk, &proof, &too_many),
+            Err(VerifyError::InputLengthMismatch {
+                expected: inputs.len(),
+                actual: inputs.len() + 1,
+            })
+        );
+        assert_eq!(
+            verify(&vk, &proof, &inputs[1..].to_vec()),
+            Err(VerifyError::InputLengthMismatch {
+                expected: inputs.len(),
+                actual: inputs.len() - 1,
+            })
+        );
+        let empty_vk = VerifyingKey { s: vec![], ..vk };
+        assert_eq!(empty_vk.check(), Err(VerifyError::InvalidVerifyingKey));
+        assert_eq!(
+            verify(&empty_vk, &proof, &vec![]),
+            Err(VerifyError::InvalidVerifyingKey)
+        );
+    }
+}
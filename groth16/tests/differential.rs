@@ -0,0 +1,151 @@
+//! Differential tests of `groth16::verify` against the arkworks verifier, on
+//! randomly generated circuits, valid proofs and tampered ones.
+
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_groth16::{prepare_verifying_key, Groth16};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::{Rng, SeedableRng};
+use ark_std::UniformRand;
+use groth16::{verify, Inputs};
+
+/// A random circuit: for each public input x_i, knowledge of a_i, b_i with
+///
+///   a_i * (b_i + c_i) = x_i
+///
+/// for constants c_i, followed by a chain of products of the witnesses.
+#[derive(Clone)]
+struct RandomCircuit {
+    constants: Vec<Fr>,
+    witnesses: Vec<(Fr, Fr)>,
+    chain_len: usize,
+}
+
+impl RandomCircuit {
+    fn random(rng: &mut StdRng) -> Self {
+        let num_inputs = rng.gen_range(0..6);
+        Self {
+            constants: (0..num_inputs).map(|_| Fr::rand(rng)).collect(),
+            witnesses: (0..num_inputs)
+                .map(|_| (Fr::rand(rng), Fr::rand(rng)))
+                .collect(),
+            chain_len: rng.gen_range(1..8),
+        }
+    }
+
+    /// The same circuit, with a new witness.
+    fn with_random_witness(&self, rng: &mut StdRng) -> Self {
+        Self {
+            witnesses: self
+                .witnesses
+                .iter()
+                .map(|_| (Fr::rand(rng), Fr::rand(rng)))
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    fn inputs(&self) -> Inputs {
+        self.constants
+            .iter()
+            .zip(&self.witnesses)
+            .map(|(c, (a, b))| *a * (*b + c))
+            .collect()
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for RandomCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let inputs = self.inputs();
+        let mut acc_value = Fr::from(1u64);
+        let mut acc = cs.new_witness_variable(|| Ok(acc_value))?;
+        cs.enforce_constraint(lc!() + acc, lc!() + acc, lc!() + acc)?;
+        for ((x, c), (a, b)) in inputs.iter().zip(&self.constants).zip(&self.witnesses) {
+            let x = cs.new_input_variable(|| Ok(*x))?;
+            let a = cs.new_witness_variable(|| Ok(*a))?;
+            let b = cs.new_witness_variable(|| Ok(*b))?;
+            cs.enforce_constraint(lc!() + a, lc!() + b + (*c, Variable::One), lc!() + x)?;
+        }
+        for i in 0..self.chain_len {
+            let factor_value = Fr::from(i as u64 + 2);
+            let factor = cs.new_witness_variable(|| Ok(factor_value))?;
+            acc_value *= factor_value;
+            let next = cs.new_witness_variable(|| Ok(acc_value))?;
+            cs.enforce_constraint(lc!() + acc, lc!() + factor, lc!() + next)?;
+            acc = next;
+        }
+        Ok(())
+    }
+}
+
+/// Check that both verifiers agree, and return the result.
+fn check_agree(
+    vk: &ark_groth16::VerifyingKey<Bn254>,
+    proof: &ark_groth16::Proof<Bn254>,
+    inputs: &[Fr],
+) -> bool {
+    let pvk = prepare_verifying_key(vk);
+    let expected = Groth16::<Bn254>::verify_proof(&pvk, proof, inputs).unwrap_or(false);
+    let actual = verify(&vk.clone().into(), &proof.clone().into(), &inputs.to_vec()).is_ok();
+    assert_eq!(
+        actual, expected,
+        "groth16::verify disagrees with ark-groth16"
+    );
+    actual
+}
+
+#[test]
+fn test_differential_ark_groth16() {
+    let mut rng = StdRng::seed_from_u64(197);
+    for _ in 0..8 {
+        let circuit = RandomCircuit::random(&mut rng);
+        let pk =
+            Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit.clone(), &mut rng)
+                .unwrap();
+        let vk = &pk.vk;
+        let prover_circuit = circuit.with_random_witness(&mut rng);
+        let inputs = prover_circuit.inputs();
+        let proof =
+            Groth16::<Bn254>::create_random_proof_with_reduction(prover_circuit, &pk, &mut rng)
+                .unwrap();
+
+        // Valid and re-randomised proofs
+        assert!(check_agree(vk, &proof, &inputs));
+        let rerandomized = Groth16::<Bn254>::rerandomize_proof(vk, &proof, &mut rng);
+        assert!(check_agree(vk, &rerandomized, &inputs));
+
+        // Wrong inputs
+        for i in 0..inputs.len() {
+            let mut bad_inputs = inputs.clone();
+            bad_inputs[i] += Fr::from(1u64);
+            assert!(!check_agree(vk, &proof, &bad_inputs));
+        }
+        let mut extra_input = inputs.clone();
+        extra_input.push(Fr::from(1u64));
+        assert!(!check_agree(vk, &proof, &extra_input));
+        if !inputs.is_empty() {
+            assert!(!check_agree(vk, &proof, &inputs[1..]));
+        }
+
+        // Tampered proofs
+        let mut bad = proof.clone();
+        bad.a = -bad.a;
+        assert!(!check_agree(vk, &bad, &inputs));
+        let mut bad = proof.clone();
+        std::mem::swap(&mut bad.a, &mut bad.c);
+        assert!(!check_agree(vk, &bad, &inputs));
+        let mut bad = proof.clone();
+        bad.c = (G1Projective::from(bad.c) + G1Affine::generator()).into_affine();
+        assert!(!check_agree(vk, &bad, &inputs));
+        let mut bad = proof.clone();
+        bad.c = G1Affine::identity();
+        assert!(!check_agree(vk, &bad, &inputs));
+
+        // A key from a different setup of the same circuit
+        let other_pk =
+            Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit, &mut rng).unwrap();
+        assert!(!check_agree(&other_pk.vk, &proof, &inputs));
+    }
+}
@@ -0,0 +1,137 @@
+//! Property tests of the JSON and primitive representations: converting any
+//! value to either representation and back gives the same value.
+
+use ark_bn254::{Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use groth16::has_json_repr::HasJsonRepr;
+use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::{Inputs, Proof, VerifyingKey};
+use proptest::prelude::*;
+use std::fmt::Debug;
+
+/// Field elements, including the edge cases 0, 1 and -1.
+fn field<F: PrimeField>() -> impl Strategy<Value = F> {
+    prop_oneof![
+        1 => Just(F::ZERO),
+        1 => Just(F::ONE),
+        1 => Just(-F::ONE),
+        7 => any::<[u8; 32]>().prop_map(|bytes| F::from_le_bytes_mod_order(&bytes)),
+    ]
+}
+
+fn fq2() -> impl Strategy<Value = Fq2> {
+    (field::<Fq>(), field::<Fq>()).prop_map(|(c0, c1)| Fq2::new(c0, c1))
+}
+
+/// G1 points, including the point at infinity (for the scalar 0).
+fn g1() -> impl Strategy<Value = G1Affine> {
+    field::<Fr>().prop_map(|k| (G1Projective::from(G1Affine::generator()) * k).into_affine())
+}
+
+/// G2 points, including the point at infinity (for the scalar 0).
+fn g2() -> impl Strategy<Value = G2Affine> {
+    field::<Fr>().prop_map(|k| (G2Projective::from(G2Affine::generator()) * k).into_affine())
+}
+
+fn proof() -> impl Strategy<Value = Proof> {
+    (g1(), g2(), g1()).prop_map(|(pi_a, pi_b, pi_c)| Proof::new_unchecked(pi_a, pi_b, pi_c))
+}
+
+fn vk() -> impl Strategy<Value = VerifyingKey> {
+    (g1(), g2(), g2(), g2(), prop::collection::vec(g1(), 1..6)).prop_map(
+        |(alpha, beta, gamma, delta, s)| VerifyingKey::new_unchecked(alpha, beta, gamma, delta, s),
+    )
+}
+
+fn inputs() -> impl Strategy<Value = Inputs> {
+    prop::collection::vec(field::<Fr>(), 0..6)
+}
+
+fn check_json<T: HasJsonRepr + PartialEq + Debug>(x: &T) -> Result<(), TestCaseError> {
+    let y = T::from_json(&x.to_json()).map_err(|e| TestCaseError::fail(format!("{e:#}")))?;
+    prop_assert_eq!(&y, x);
+    Ok(())
+}
+
+fn check_repr<T: HasPrimitiveRepr + PartialEq + Debug>(x: &T) -> Result<(), TestCaseError> {
+    let y = T::try_from_repr(&x.to_repr());
+    prop_assert_eq!(y.as_ref(), Ok(x));
+    prop_assert_eq!(&T::from_repr(&x.to_repr()), x);
+    Ok(())
+}
+
+/// The encodings of a field element accepted by the JSON parser: decimal, and
+/// "0x"-prefixed hex (big-endian, with or without leading zeros, in either
+/// case).
+fn encodings<F: PrimeField>(x: F) -> Vec<String> {
+    let hex = hex::encode(x.into_bigint().to_bytes_be());
+    let trimmed = hex.trim_start_matches('0');
+    vec![
+        x.into_bigint().to_string(),
+        format!("0x{hex}"),
+        format!("0x{}", hex.to_uppercase()),
+        format!("0x{}", if trimmed.is_empty() { "0" } else { trimmed }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn test_fr_round_trip(x in field::<Fr>()) {
+        check_json(&x)?;
+        check_repr(&x)?;
+        for repr in encodings(x) {
+            prop_assert_eq!(Fr::from_json(&repr).unwrap(), x, "{}", repr);
+        }
+    }
+
+    #[test]
+    fn test_fq_round_trip(x in field::<Fq>()) {
+        check_json(&x)?;
+        check_repr(&x)?;
+        for repr in encodings(x) {
+            prop_assert_eq!(Fq::from_json(&repr).unwrap(), x, "{}", repr);
+        }
+    }
+
+    #[test]
+    fn test_fq2_round_trip(x in fq2()) {
+        check_json(&x)?;
+        check_repr(&x)?;
+    }
+
+    #[test]
+    fn test_g1_round_trip(p in g1()) {
+        check_json(&p)?;
+        check_repr(&p)?;
+    }
+
+    #[test]
+    fn test_g2_round_trip(p in g2()) {
+        check_json(&p)?;
+        check_repr(&p)?;
+    }
+
+    #[test]
+    fn test_inputs_round_trip(inputs in inputs()) {
+        check_json(&inputs)?;
+        check_repr(&inputs)?;
+    }
+}
+
+proptest! {
+    // Fewer cases, since each needs several G2 scalar multiplications
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_proof_round_trip(proof in proof()) {
+        check_json(&proof)?;
+        check_repr(&proof)?;
+    }
+
+    #[test]
+    fn test_vk_round_trip(vk in vk()) {
+        check_json(&vk)?;
+        check_repr(&vk)?;
+    }
+}
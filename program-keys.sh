@@ -0,0 +1,74 @@
+#!/usr/bin/env bash
+#
+# Print the SP1 program verification key and the RISC Zero image IDs (of the
+# default and streaming guests), and write them to program-keys.json along
+# with the SHA-256 hashes of the guest ELFs.
+#
+# The SP1 ELF is checked in, so it is rebuilt (with `cargo prove build
+# --docker`, for a reproducible build) and the script fails, before printing
+# any keys, if the fresh build does not match the file in the working tree.
+# The file is restored afterwards either way. The manifest is not tracked.
+#
+# Usage (from the repository root): ./program-keys.sh
+
+set -euo pipefail
+
+cd "$(dirname "$0")"
+
+SP1_ELF=test-sp1/program/elf/riscv32im-succinct-zkvm-elf
+MANIFEST=program-keys.json
+
+# SHA-256 of stdin, 0x-prefixed
+sha256() {
+    echo "0x$(sha256sum | cut -d' ' -f1)"
+}
+
+# SP1: rebuild the program and compare with the ELF in the working tree,
+# which the build overwrites. Keep a copy to restore on exit.
+sp1_elf_copy=$(mktemp)
+cp "${SP1_ELF}" "${sp1_elf_copy}"
+trap 'cp "${sp1_elf_copy}" "${SP1_ELF}"; rm -f "${sp1_elf_copy}"' EXIT
+expected_sp1_elf_hash=$(sha256 < "${sp1_elf_copy}")
+(cd test-sp1/program && cargo prove build --docker)
+sp1_elf_hash=$(sha256 < "${SP1_ELF}")
+if [ "${expected_sp1_elf_hash}" != "${sp1_elf_hash}" ]; then
+    echo "error: SP1 ELF (${expected_sp1_elf_hash}) does not match a fresh build (${sp1_elf_hash})" >&2
+    echo "Rebuild it with \`cargo prove build --docker\` in test-sp1/program, commit it and run the script again." >&2
+    exit 1
+fi
+# The script's build.rs would otherwise rebuild the ELF without docker.
+(cd test-sp1/script && SP1_SKIP_PROGRAM_BUILD=true cargo build --release --bin vkey)
+sp1_vkey=$(cd test-sp1/script && ./target/release/vkey \
+    | sed -n 's/^Program Verification Key: //p')
+
+# RISC Zero: the guest ELFs are built as part of the host build (set
+# RISC0_USE_DOCKER=1 for a reproducible build).
+(cd test-risc0 && cargo build --release --bin image_id)
+risc0_output=$(cd test-risc0 && ./target/release/image_id)
+risc0_image_id=$(echo "${risc0_output}" | sed -n 's/^Image ID: //p')
+risc0_elf_hash=$(echo "${risc0_output}" | sed -n 's/^ELF SHA-256: //p')
+risc0_streaming_image_id=$(echo "${risc0_output}" | sed -n 's/^Streaming Image ID: //p')
+risc0_streaming_elf_hash=$(echo "${risc0_output}" | sed -n 's/^Streaming ELF SHA-256: //p')
+
+echo "SP1 program verification key: ${sp1_vkey}"
+echo "SP1 ELF SHA-256: ${sp1_elf_hash}"
+echo "RiscZero image ID: ${risc0_image_id}"
+echo "RiscZero ELF SHA-256: ${risc0_elf_hash}"
+echo "RiscZero streaming image ID: ${risc0_streaming_image_id}"
+echo "RiscZero streaming ELF SHA-256: ${risc0_streaming_elf_hash}"
+
+cat > "${MANIFEST}" <<JSON
+{
+  "sp1": {
+    "vkey": "${sp1_vkey}",
+    "elf_sha256": "${sp1_elf_hash}"
+  },
+  "risc0": {
+    "image_id": "${risc0_image_id}",
+    "elf_sha256": "${risc0_elf_hash}",
+    "streaming_image_id": "${risc0_streaming_image_id}",
+    "streaming_elf_sha256": "${risc0_streaming_elf_hash}"
+  }
+}
+JSON
+echo "Manifest saved to file: ${MANIFEST}"
@@ -0,0 +1,101 @@
+//! Export a saved Groth16 aggregation receipt as calldata for the RISC Zero
+//! verifier contract (`IRiscZeroVerifier.verify`).
+//!
+//! ```sh
+//! ./target/release/export --receipt ./_test_data/risc0_agg_proof_batch_size_1.g16
+//! ```
+use alloy_primitives::B256;
+use alloy_sol_types::{sol, SolCall};
+use anyhow::{Context, Result};
+use clap::Parser;
+use host::guest;
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::Receipt;
+use serde::Serialize;
+use std::path::PathBuf;
+
+sol! {
+    interface IRiscZeroVerifier {
+        function verify(bytes calldata seal, bytes32 imageId, bytes32 journalDigest) external view;
+    }
+}
+
+/// The arguments for the export command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct ExportArgs {
+    /// Groth16 receipt file, as written by the host
+    #[clap(long)]
+    receipt: PathBuf,
+
+    /// The receipt was produced with `--streaming`, by the streaming guest
+    #[clap(long)]
+    streaming: bool,
+
+    /// Output file (defaults to the receipt file with a `.calldata.json`
+    /// extension)
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+/// Hex-encoded (0x-prefixed) arguments and calldata for `verify`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CalldataJson {
+    function: String,
+    seal: String,
+    image_id: String,
+    journal_digest: String,
+    journal: String,
+    calldata: String,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn main() -> Result<()> {
+    let args = ExportArgs::parse();
+
+    let bytes = std::fs::read(&args.receipt)
+        .with_context(|| format!("failed to read {}", args.receipt.display()))?;
+    let receipt: Receipt = bincode::deserialize(&bytes).context("failed to decode receipt")?;
+    let (_, image_id) = guest(args.streaming);
+    receipt
+        .verify(image_id)
+        .context("failed to verify receipt")?;
+    let groth16_receipt = receipt
+        .inner
+        .groth16()
+        .context("receipt must be produced with --mode groth16")?;
+
+    // The verifier router selects a verifier using the first 4 bytes of the
+    // verifier parameters digest, prepended to the seal.
+    let mut seal = groth16_receipt.verifier_parameters.as_bytes()[..4].to_vec();
+    seal.extend_from_slice(&groth16_receipt.seal);
+    let image_id = Digest::from(image_id);
+    let journal_digest = receipt.journal.digest();
+
+    let call = IRiscZeroVerifier::verifyCall {
+        seal: seal.clone().into(),
+        imageId: B256::from_slice(image_id.as_bytes()),
+        journalDigest: B256::from_slice(journal_digest.as_bytes()),
+    };
+    let calldata = call.abi_encode();
+
+    let json = CalldataJson {
+        function: IRiscZeroVerifier::verifyCall::SIGNATURE.to_string(),
+        seal: to_hex(&seal),
+        image_id: to_hex(image_id.as_bytes()),
+        journal_digest: to_hex(journal_digest.as_bytes()),
+        journal: to_hex(&receipt.journal.bytes),
+        calldata: to_hex(&calldata),
+    };
+    let output = args
+        .output
+        .unwrap_or_else(|| args.receipt.with_extension("calldata.json"));
+    std::fs::write(&output, serde_json::to_string_pretty(&json)?)
+        .with_context(|| format!("failed to write {}", output.display()))?;
+    println!("Calldata saved to file: {}", output.display());
+    Ok(())
+}
@@ -0,0 +1,16 @@
+//! Print the image IDs of the default and streaming guests and the SHA-256
+//! hashes of their ELFs.
+//!
+//! ```sh
+//! ./target/release/image_id
+//! ```
+use host::guest;
+use risc0_zkvm::sha::{Digest, Impl, Sha256};
+
+fn main() {
+    for (label, streaming) in [("", false), ("Streaming ", true)] {
+        let (elf, image_id) = guest(streaming);
+        println!("{label}Image ID: 0x{}", Digest::from(image_id));
+        println!("{label}ELF SHA-256: 0x{}", Impl::hash_bytes(elf));
+    }
+}
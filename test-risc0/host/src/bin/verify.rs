@@ -0,0 +1,54 @@
+//! Verify a saved aggregation receipt against the guest image ID.
+//!
+//! ```sh
+//! ./target/release/verify --receipt ./_test_data/risc0_agg_proof_batch_size_1.g16
+//! ```
+use clap::Parser;
+use host::guest;
+use risc0_zkvm::Receipt;
+use std::path::PathBuf;
+use std::process::exit;
+
+/// The arguments for the verify command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct VerifyArgs {
+    /// Receipt file, as written by the host
+    #[clap(long)]
+    receipt: PathBuf,
+
+    /// The receipt was produced with `--streaming`, by the streaming guest
+    #[clap(long)]
+    streaming: bool,
+}
+
+fn main() {
+    let args = VerifyArgs::parse();
+
+    let receipt_bytes = std::fs::read(&args.receipt).unwrap_or_else(|e| {
+        eprintln!("Failed to read {}: {e}", args.receipt.display());
+        exit(1)
+    });
+    let receipt: Receipt = bincode::deserialize(&receipt_bytes).unwrap_or_else(|e| {
+        eprintln!("Failed to decode receipt: {e}");
+        exit(1)
+    });
+
+    let (_, image_id) = guest(args.streaming);
+    if let Err(e) = receipt.verify(image_id) {
+        eprintln!("Receipt verification failed: {e}");
+        exit(1)
+    }
+    println!("Receipt verified");
+
+    // Receipts from older guests commit nothing.
+    if receipt.journal.bytes.is_empty() {
+        println!("No committed outputs");
+        return;
+    }
+    let batch_size: u32 = receipt.journal.decode().unwrap_or_else(|e| {
+        eprintln!("Failed to decode journal: {e}");
+        exit(1)
+    });
+    println!("Batch size: {batch_size}");
+}
@@ -0,0 +1,104 @@
+use groth16::batch::Batch;
+use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::input_header::InputHeader;
+use methods::{
+    RISC0_GROTH16_AGGREGATION_ELF, RISC0_GROTH16_AGGREGATION_ID,
+    RISC0_GROTH16_AGGREGATION_STREAMING_ELF, RISC0_GROTH16_AGGREGATION_STREAMING_ID,
+};
+use risc0_zkvm::ExecutorEnvBuilder;
+use std::collections::BTreeMap;
+
+pub mod profile;
+
+/// The ELF and image ID of the guest to run: the streaming guest, whose
+/// allocator frees memory, for streaming input, and otherwise the default
+/// guest, whose bump allocator never frees memory.
+pub fn guest(streaming: bool) -> (&'static [u8], [u32; 8]) {
+    if streaming {
+        (
+            RISC0_GROTH16_AGGREGATION_STREAMING_ELF,
+            RISC0_GROTH16_AGGREGATION_STREAMING_ID,
+        )
+    } else {
+        (RISC0_GROTH16_AGGREGATION_ELF, RISC0_GROTH16_AGGREGATION_ID)
+    }
+}
+
+/// The measurements printed by the guest at the end of a run.
+pub struct GuestReport {
+    /// Cycles spent in each phase
+    pub cycles: BTreeMap<String, u64>,
+    /// Peak heap usage, in bytes
+    pub peak_heap: u64,
+    /// Whether `peak_heap` is only a lower bound, as sampled by the streaming
+    /// guest, rather than the exact peak
+    pub peak_heap_is_lower_bound: bool,
+    /// Cycles spent by the guest measuring its heap usage, which are included
+    /// in the user cycles of the run but not in any phase
+    pub heap_probe_cycles: u64,
+}
+
+impl GuestReport {
+    /// Parse the report from the guest's stdout.
+    pub fn parse(stdout: &str) -> anyhow::Result<Self> {
+        let mut cycles = BTreeMap::new();
+        let (mut peak_heap, mut heap_probe_cycles) = (None, None);
+        let mut peak_heap_is_lower_bound = false;
+        for line in stdout.lines() {
+            if let Some(report) = line.strip_prefix("cycle-tracker-report: ") {
+                let (phase, count) = report
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow::Error::msg(format!("invalid report: {line}")))?;
+                cycles.insert(phase.to_string(), count.parse()?);
+            } else if let Some(report) = line.strip_prefix("heap-report: ") {
+                let bytes = match report.strip_suffix(" (lower bound)") {
+                    Some(bytes) => {
+                        peak_heap_is_lower_bound = true;
+                        bytes
+                    }
+                    None => report,
+                };
+                peak_heap = Some(bytes.parse()?);
+            } else if let Some(count) = line.strip_prefix("heap-probe-report: ") {
+                heap_probe_cycles = Some(count.parse()?);
+            }
+        }
+        Ok(Self {
+            cycles,
+            peak_heap: peak_heap.ok_or_else(|| anyhow::Error::msg("no heap report"))?,
+            peak_heap_is_lower_bound,
+            heap_probe_cycles: heap_probe_cycles
+                .ok_or_else(|| anyhow::Error::msg("no heap probe report"))?,
+        })
+    }
+
+    /// The peak heap usage, in bytes, labelled if it is only a lower bound.
+    pub fn peak_heap_label(&self) -> String {
+        if self.peak_heap_is_lower_bound {
+            format!("{} bytes (lower bound)", self.peak_heap)
+        } else {
+            format!("{} bytes", self.peak_heap)
+        }
+    }
+}
+
+/// Write the guest input: an `InputHeader`, followed by the verification keys
+/// of the batch and the proofs to be verified (each with its inputs and the
+/// index of its key).
+///
+/// In streaming mode the keys are written first and then each proof
+/// separately, so that the guest can verify each proof before reading the
+/// next. Otherwise the batch is written as a whole.
+pub fn write_guest_input(env_builder: &mut ExecutorEnvBuilder, batch: &Batch, streaming: bool) {
+    env_builder
+        .write(&InputHeader::new(batch, streaming))
+        .unwrap();
+    if streaming {
+        env_builder.write(&batch.vks.to_repr()).unwrap();
+        for entry in &batch.entries {
+            env_builder.write(&entry.to_repr()).unwrap();
+        }
+    } else {
+        env_builder.write(&batch.to_repr()).unwrap();
+    }
+}
@@ -1,18 +1,47 @@
 // These constants represent the RISC-V ELF and the image ID generated by risc0-build.
 // The ELF is used for proving and the ID is used for verification.
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use groth16::batch::Batch;
 use groth16::has_json_repr::load_json;
-use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::sample::{SAMPLE_INPUTS_FILE, SAMPLE_PROOF_FILE, SAMPLE_VK_FILE};
 use groth16::{Inputs, Proof, VerifyingKey};
-use methods::{RISC0_GROTH16_AGGREGATION_ELF, RISC0_GROTH16_AGGREGATION_ID};
-use risc0_zkvm::{default_prover, ExecutorEnv, ProverOpts};
+use host::{guest, profile, write_guest_input, GuestReport};
+use risc0_zkvm::{default_executor, default_prover, ExecutorEnv, ProverOpts, Receipt};
 use std::fs::File;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-const SAMPLE_INPUTS_FILE: &str = "../groth16/src/data/inputs.json";
-const SAMPLE_PROOF_FILE: &str = "../groth16/src/data/proof.json";
-const SAMPLE_VK_FILE: &str = "../groth16/src/data/vk.json";
+/// Default directory for profiles and logs, in `test-risc0`
+const RESULTS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../results");
+/// Default directory for proofs, in `test-risc0`
+const TEST_DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../_test_data");
+/// Segment limit po2 the executor uses when none is set, as
+/// `DEFAULT_SEGMENT_LIMIT_PO2` in risc0-circuit-rv32im
+const DEFAULT_SEGMENT_LIMIT_PO2: u32 = 20;
+
+/// The kind of receipt to produce. Each mode includes the stages of the
+/// previous one.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, ValueEnum)]
+enum Mode {
+    /// One STARK per segment
+    Composite,
+    /// Segment STARKs recursively compressed into a single STARK
+    Succinct,
+    /// Succinct STARK wrapped in a Groth16 proof, for on-chain verification
+    Groth16,
+}
+
+impl Mode {
+    /// Extension of the saved receipt file
+    fn extension(self) -> &'static str {
+        match self {
+            Mode::Composite => "composite",
+            Mode::Succinct => "succinct",
+            Mode::Groth16 => "g16",
+        }
+    }
+}
 
 /// The arguments for the prove command.
 #[derive(Parser, Debug)]
@@ -21,6 +50,67 @@ struct ProveArgs {
     /// Batch size
     #[clap(long, default_value = "1")]
     n: u32,
+
+    /// Verification key JSON file
+    #[clap(long, default_value = SAMPLE_VK_FILE)]
+    vk: String,
+
+    /// Proof JSON file, verified `n` times
+    #[clap(long, default_value = SAMPLE_PROOF_FILE)]
+    proof: String,
+
+    /// Public inputs JSON file
+    #[clap(long, default_value = SAMPLE_INPUTS_FILE)]
+    inputs: String,
+
+    /// Re-randomise each copy of the proof, so that the batch contains `n`
+    /// distinct proofs
+    #[clap(long)]
+    rerandomize: bool,
+
+    /// Seed for the re-randomisation scalars
+    #[clap(long, default_value_t = 0, requires = "rerandomize")]
+    seed: u64,
+
+    /// Send the proofs to the guest one at a time, verifying each before the
+    /// next is read, instead of reading the whole batch up front
+    #[clap(long)]
+    streaming: bool,
+
+    /// JSON Lines file of application proofs to verify, instead of repeating
+    /// a single proof
+    #[clap(
+        long,
+        requires = "vk_table",
+        conflicts_with_all = ["n", "vk", "proof", "inputs", "rerandomize"]
+    )]
+    batch: Option<String>,
+
+    /// JSON table of the verification keys referred to by the `--batch`
+    /// proofs
+    #[clap(long)]
+    vk_table: Option<String>,
+
+    /// Kind of receipt to produce
+    #[clap(long, value_enum, default_value = "groth16")]
+    mode: Mode,
+
+    /// Log2 of the maximum number of cycles per segment
+    #[clap(long, default_value_t = DEFAULT_SEGMENT_LIMIT_PO2)]
+    segment_limit_po2: u32,
+
+    /// Execute with the profiler enabled and write a flamegraph of the guest
+    /// to the profile directory, instead of generating a proof
+    #[clap(long)]
+    profile: bool,
+
+    /// Directory in which to save the profile and flamegraph
+    #[clap(long, default_value = RESULTS_DIR)]
+    profile_dir: PathBuf,
+
+    /// Directory in which to save the receipt
+    #[clap(long, default_value = TEST_DATA_DIR)]
+    output_dir: PathBuf,
 }
 
 fn main() {
@@ -30,52 +120,144 @@ fn main() {
         .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
         .init();
 
-    // Read input from JSON
-    let inputs: Inputs = load_json(SAMPLE_INPUTS_FILE);
-    let proof: Proof = load_json(SAMPLE_PROOF_FILE);
-    let groth16_vk: VerifyingKey = load_json(SAMPLE_VK_FILE);
-
-    // Parse batch size from command line
     let args = ProveArgs::parse();
-    let batch_size: u32 = args.n;
+
+    // Read the batch from JSON
+    let batch = match &args.batch {
+        Some(proofs_file) => Batch::load(proofs_file, args.vk_table.as_ref().unwrap()).unwrap(),
+        None => {
+            let inputs: Inputs = load_json(&args.inputs);
+            let proof: Proof = load_json(&args.proof);
+            let groth16_vk: VerifyingKey = load_json(&args.vk);
+            if args.rerandomize {
+                Batch::rerandomized(groth16_vk, inputs, proof, args.n, args.seed)
+            } else {
+                Batch::repeated(groth16_vk, inputs, proof, args.n)
+            }
+        }
+    };
+    let batch_size = batch.len() as u32;
     println!("Batch size: {batch_size}");
+    println!("Verification keys: {}", batch.vks.len());
+    println!("Streaming input: {}", args.streaming);
+    println!("Segment limit po2: {}", args.segment_limit_po2);
 
-    let env = ExecutorEnv::builder()
-        .write(&batch_size)
-        .unwrap()
-        .write(&inputs.to_repr())
-        .unwrap()
-        .write(&proof.to_repr())
-        .unwrap()
-        .write(&groth16_vk.to_repr())
-        .unwrap()
-        .build()
-        .unwrap();
+    let (elf, image_id) = guest(args.streaming);
+    let profile_file = args
+        .profile_dir
+        .join(format!("risc0_batchsize_{batch_size}.pb"));
+    // The guest's stdout is captured, to read the measurements it reports
+    let mut stdout = Vec::new();
+    let env = {
+        let mut env_builder = ExecutorEnv::builder();
+        write_guest_input(&mut env_builder, &batch, args.streaming);
+        env_builder.stdout(&mut stdout);
+        env_builder.segment_limit_po2(args.segment_limit_po2);
+        if args.profile {
+            std::fs::create_dir_all(&args.profile_dir).unwrap();
+            env_builder.enable_profiler(&profile_file);
+        }
+        env_builder.build().unwrap()
+    };
+
+    if args.profile {
+        run_profiler(env, elf, batch_size, &args.profile_dir, &profile_file);
+        print!("{}", String::from_utf8_lossy(&stdout));
+        return;
+    }
 
     // Obtain the default prover.
     let prover = default_prover();
-    // Produce a G16 proof, for on-chain verification
-    let prover_opts = ProverOpts::groth16();
+    println!("Proof mode: {:?}", args.mode);
 
     // Proof information by proving the specified ELF binary.
     // This struct contains the receipt along with statistics about execution of the guest
     let now = Instant::now();
+    let stage = Instant::now();
     let prove_info = prover
-        .prove_with_opts(env, RISC0_GROTH16_AGGREGATION_ELF, &prover_opts)
+        .prove_with_opts(env, elf, &ProverOpts::default())
         .unwrap();
-    println!("Proof generation time: {}s", now.elapsed().as_secs());
+    let mut receipt = prove_info.receipt;
+    print_stage("composite", stage, &receipt);
+
+    // Report the cycles and heap usage of the guest.
+    let stdout = String::from_utf8_lossy(&stdout);
+    print!("{stdout}");
+    let report = GuestReport::parse(&stdout).unwrap();
+    println!("Total cycles: {}", prove_info.stats.total_cycles);
+    println!(
+        "User cycles: {}",
+        prove_info.stats.user_cycles - report.heap_probe_cycles
+    );
+    for (phase, cycles) in &report.cycles {
+        println!("Cycles {phase}: {cycles}");
+    }
+    println!("Peak heap: {}", report.peak_heap_label());
+
+    // Compress the segment receipts into a single STARK.
+    if args.mode >= Mode::Succinct {
+        let stage = Instant::now();
+        receipt = prover.compress(&ProverOpts::succinct(), &receipt).unwrap();
+        print_stage("succinct", stage, &receipt);
+    }
 
-    // extract the receipt.
-    let receipt = prove_info.receipt;
+    // Wrap the STARK in a Groth16 proof, for on-chain verification.
+    if args.mode >= Mode::Groth16 {
+        let stage = Instant::now();
+        receipt = prover.compress(&ProverOpts::groth16(), &receipt).unwrap();
+        print_stage("groth16", stage, &receipt);
+    }
+    println!("Proof generation time: {:.2}s", now.elapsed().as_secs_f64());
 
     // Save the proof to a file.
-    let filename = format!("./_test_data/risc0_agg_proof_batch_size_{}.g16", batch_size);
+    std::fs::create_dir_all(&args.output_dir).unwrap();
+    let filename = args.output_dir.join(format!(
+        "risc0_agg_proof_batch_size_{}.{}",
+        batch_size,
+        args.mode.extension()
+    ));
     let receipt_bytes = bincode::serialize(&receipt).unwrap();
     let mut file = File::create(&filename).unwrap();
     file.write_all(&receipt_bytes).unwrap();
-    println!("Proof saved to file: {}", filename);
+    println!("Proof saved to file: {}", filename.display());
 
     // The receipt was verified at the end of proving, but the below code is an
     // example of how someone else could verify this receipt.
-    receipt.verify(RISC0_GROTH16_AGGREGATION_ID).unwrap();
+    receipt.verify(image_id).unwrap();
+}
+
+/// Report the time taken by a proving stage and the size of its receipt.
+fn print_stage(name: &str, start: Instant, receipt: &Receipt) {
+    println!("Stage {name} time: {:.2}s", start.elapsed().as_secs_f64());
+    println!(
+        "Stage {name} receipt size: {} bytes",
+        bincode::serialize(receipt).unwrap().len()
+    );
+}
+
+/// Execute the guest with the profiler enabled and render the recorded call
+/// stacks as `<profile_dir>/risc0_batchsize_<n>_flamegraph.svg`.
+fn run_profiler(
+    env: ExecutorEnv,
+    elf: &[u8],
+    batch_size: u32,
+    profile_dir: &Path,
+    profile_file: &Path,
+) {
+    let svg_file = profile_dir.join(format!("risc0_batchsize_{batch_size}_flamegraph.svg"));
+
+    let now = Instant::now();
+    let session = default_executor().execute(env, elf).unwrap();
+    println!("Execution time: {:.2}s", now.elapsed().as_secs_f64());
+    println!("Number of segments: {}", session.segments.len());
+
+    let folded = profile::fold_profile(profile_file).unwrap();
+    profile::write_flamegraph(
+        &folded,
+        &format!("RiscZero Groth16 aggregation, batch size {batch_size}"),
+        &svg_file,
+    )
+    .unwrap();
+    println!("Profile saved to file: {}", profile_file.display());
+    println!("Flamegraph saved to file: {}", svg_file.display());
 }
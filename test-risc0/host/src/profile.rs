@@ -0,0 +1,119 @@
+//! Render flamegraphs from RISC Zero guest profiles.
+//!
+//! The RISC Zero executor can record the guest call stack in pprof format.
+//! We decode the samples and render them with inferno, so that no Go
+//! toolchain is needed to inspect the profile.
+
+use prost::Message;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Result};
+use std::path::Path;
+
+// The subset of the pprof `Profile` message (see
+// https://github.com/google/pprof/blob/main/proto/profile.proto) needed to
+// reconstruct call stacks.
+
+#[derive(Clone, PartialEq, Message)]
+struct Profile {
+    #[prost(message, repeated, tag = "2")]
+    sample: Vec<Sample>,
+    #[prost(message, repeated, tag = "4")]
+    location: Vec<Location>,
+    #[prost(message, repeated, tag = "5")]
+    function: Vec<Function>,
+    #[prost(string, repeated, tag = "6")]
+    string_table: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Sample {
+    /// Leaf first
+    #[prost(uint64, repeated, tag = "1")]
+    location_id: Vec<u64>,
+    #[prost(int64, repeated, tag = "2")]
+    value: Vec<i64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Location {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    /// Inlined frames, with the caller last
+    #[prost(message, repeated, tag = "4")]
+    line: Vec<Line>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Line {
+    #[prost(uint64, tag = "1")]
+    function_id: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Function {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    /// Index into the string table
+    #[prost(int64, tag = "2")]
+    name: i64,
+}
+
+/// Read the pprof profile at `profile_path` and return its samples as folded
+/// stacks (one `frame;frame;frame count` line per distinct stack).
+pub fn fold_profile(profile_path: &Path) -> Result<Vec<String>> {
+    let bytes = std::fs::read(profile_path)?;
+    let profile =
+        Profile::decode(bytes.as_slice()).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+    let function_names: HashMap<u64, String> = profile
+        .function
+        .iter()
+        .map(|f| {
+            let name = profile
+                .string_table
+                .get(f.name as usize)
+                .map(String::as_str)
+                .unwrap_or("<unknown>");
+            // `;` separates frames in the folded stack format
+            let name = format!("{:#}", rustc_demangle::demangle(name)).replace(';', ",");
+            (f.id, name)
+        })
+        .collect();
+    let locations: HashMap<u64, &Location> = profile.location.iter().map(|l| (l.id, l)).collect();
+
+    let mut stacks: HashMap<String, i64> = HashMap::new();
+    for sample in &profile.sample {
+        let frames: Vec<&str> = sample
+            .location_id
+            .iter()
+            .rev()
+            .filter_map(|id| locations.get(id))
+            .flat_map(|location| location.line.iter().rev())
+            .map(|line| {
+                function_names
+                    .get(&line.function_id)
+                    .map(String::as_str)
+                    .unwrap_or("<unknown>")
+            })
+            .collect();
+        let value = sample.value.first().copied().unwrap_or(0);
+        *stacks.entry(frames.join(";")).or_default() += value;
+    }
+
+    Ok(stacks
+        .into_iter()
+        .filter(|(frames, _)| !frames.is_empty())
+        .map(|(frames, count)| format!("{frames} {count}"))
+        .collect())
+}
+
+/// Render folded stacks as a flamegraph SVG at `svg_path`.
+pub fn write_flamegraph(folded: &[String], title: &str, svg_path: &Path) -> Result<()> {
+    let mut options = inferno::flamegraph::Options::default();
+    options.title = title.to_string();
+    options.count_name = "cycles".to_string();
+    let writer = BufWriter::new(File::create(svg_path)?);
+    inferno::flamegraph::from_lines(&mut options, folded.iter().map(String::as_str), writer)
+        .map_err(Error::other)
+}
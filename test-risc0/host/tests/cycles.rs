@@ -0,0 +1,51 @@
+//! Check the guest cycle counts against the baseline in
+//! `tests/cycles_baseline.json`, failing if any phase has become more
+//! expensive or has no baseline. Run with `UPDATE_CYCLE_BASELINE=1` to record
+//! a new baseline.
+use groth16::batch::Batch;
+use groth16::cycle_baseline::{check_cycle_baseline, Baseline, Cycles, BATCH_SIZES};
+use groth16::sample::sample_batch;
+use host::{guest, write_guest_input, GuestReport};
+use risc0_zkvm::{default_executor, ExecutorEnv};
+
+const BASELINE_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cycles_baseline.json");
+
+/// Execute the guest, returning the user cycles of the whole run and the
+/// cycles of each phase reported by the guest, and the peak heap usage
+/// reported by the guest.
+fn measure(batch: &Batch, streaming: bool) -> (Cycles, String) {
+    let (elf, _) = guest(streaming);
+    let mut stdout = Vec::new();
+    let session = {
+        let mut env_builder = ExecutorEnv::builder();
+        write_guest_input(&mut env_builder, batch, streaming);
+        env_builder.stdout(&mut stdout);
+        let env = env_builder.build().unwrap();
+        default_executor().execute(env, elf).unwrap()
+    };
+
+    let report = GuestReport::parse(&String::from_utf8(stdout).unwrap()).unwrap();
+    let mut cycles = report.cycles.clone();
+    // Each segment reports its user cycles, without the paging and padding
+    // overhead included in the executor's total cycles. The cycles the guest
+    // spent measuring its heap are not counted.
+    let user: u64 = session.segments.iter().map(|s| s.cycles as u64).sum();
+    cycles.insert("user".to_string(), user - report.heap_probe_cycles);
+    (cycles, report.peak_heap_label())
+}
+
+#[test]
+fn test_cycle_counts() {
+    // Runs are labelled by batch size, with streaming runs marked as such.
+    let mut measured = Baseline::new();
+    for n in BATCH_SIZES {
+        let batch = sample_batch(n);
+        let (cycles, heap) = measure(&batch, false);
+        let (streaming_cycles, streaming_heap) = measure(&batch, true);
+        println!("batch size {n}: heap {heap}, {streaming_heap} streaming");
+        measured.insert(format!("{n}"), cycles);
+        measured.insert(format!("{n} streaming"), streaming_cycles);
+    }
+
+    check_cycle_baseline(BASELINE_FILE, &measured);
+}
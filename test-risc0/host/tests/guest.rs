@@ -0,0 +1,111 @@
+//! Run the guest with the dev-mode prover, which executes the guest but skips
+//! proof generation.
+use ark_bn254::Fr;
+use ark_ff::One;
+use groth16::batch::Batch;
+use groth16::error::VerifyError;
+use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::input_header::{InputHeader, INPUT_VERSION};
+use groth16::invalid_cases::{load_invalid_cases, INVALID_CASES_FILE};
+use groth16::sample::{sample_batch, SAMPLE_APPLICATION_PROOFS_FILE, SAMPLE_VK_TABLE_FILE};
+use host::{guest, write_guest_input};
+use methods::{RISC0_GROTH16_AGGREGATION_ELF, RISC0_GROTH16_AGGREGATION_ID};
+use risc0_zkvm::{default_executor, default_prover, ExecutorEnv, Receipt};
+
+fn prove_dev_mode(batch: &Batch, streaming: bool) -> anyhow::Result<Receipt> {
+    std::env::set_var("RISC0_DEV_MODE", "1");
+    let mut env_builder = ExecutorEnv::builder();
+    write_guest_input(&mut env_builder, batch, streaming);
+    let env = env_builder.build().unwrap();
+    let (elf, _) = guest(streaming);
+    Ok(default_prover().prove(env, elf)?.receipt)
+}
+
+#[test]
+fn test_guest_valid_proof() {
+    let batch = sample_batch(2);
+
+    for streaming in [false, true] {
+        let receipt = prove_dev_mode(&batch, streaming).unwrap();
+        let (_, image_id) = guest(streaming);
+        receipt.verify(image_id).unwrap();
+        let batch_size: u32 = receipt.journal.decode().unwrap();
+        assert_eq!(batch_size, 2);
+    }
+}
+
+#[test]
+fn test_guest_invalid_proof() {
+    // Failure case
+    let mut batch = sample_batch(1);
+    batch.entries[0].inputs[0] = Fr::one();
+    let err = prove_dev_mode(&batch, false).unwrap_err();
+    let reason = format!("proof 0: {}", VerifyError::PairingMismatch);
+    assert!(err.to_string().contains(&reason), "{err}");
+}
+
+#[test]
+fn test_guest_invalid_cases() {
+    // Each case is rejected by the guest with the expected reason
+    for case in load_invalid_cases(INVALID_CASES_FILE) {
+        let (header, batch_repr) = case.guest_input().unwrap();
+        let env = ExecutorEnv::builder()
+            .write(&header)
+            .unwrap()
+            .write(&batch_repr)
+            .unwrap()
+            .build()
+            .unwrap();
+        let err = default_executor()
+            .execute(env, RISC0_GROTH16_AGGREGATION_ELF)
+            .unwrap_err();
+        let reason = case.expected.to_string();
+        assert!(err.to_string().contains(&reason), "{}: {err}", case.name);
+    }
+}
+
+#[test]
+fn test_guest_vk_index_out_of_range() {
+    // The second proof refers to a verification key which is not in the batch
+    let mut batch = sample_batch(2);
+    batch.entries[1].vk_index = 1;
+    for streaming in [false, true] {
+        let err = prove_dev_mode(&batch, streaming).unwrap_err();
+        assert!(
+            err.to_string().contains("proof 1: verification key index"),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn test_guest_application_proofs() {
+    let batch = Batch::load(SAMPLE_APPLICATION_PROOFS_FILE, SAMPLE_VK_TABLE_FILE).unwrap();
+
+    let receipt = prove_dev_mode(&batch, false).unwrap();
+    receipt.verify(RISC0_GROTH16_AGGREGATION_ID).unwrap();
+    let batch_size: u32 = receipt.journal.decode().unwrap();
+    assert_eq!(batch_size, batch.len() as u32);
+}
+
+#[test]
+fn test_guest_input_version_mismatch() {
+    let batch = sample_batch(1);
+
+    // Input written for a different version of the guest
+    let header = InputHeader {
+        version: INPUT_VERSION + 1,
+        ..InputHeader::new(&batch, false)
+    };
+    let env = ExecutorEnv::builder()
+        .write(&header)
+        .unwrap()
+        .write(&batch.to_repr())
+        .unwrap()
+        .build()
+        .unwrap();
+    let err = default_executor()
+        .execute(env, RISC0_GROTH16_AGGREGATION_ELF)
+        .unwrap_err();
+    assert!(err.to_string().contains("input version"), "{err}");
+}
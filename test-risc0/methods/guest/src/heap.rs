@@ -0,0 +1,104 @@
+//! Peak heap usage of the guest.
+//!
+//! The default guest uses the platform's bump allocator, which never frees
+//! memory, so the memory it has allocated is the peak heap usage. The
+//! streaming guest is built with the `heap-embedded-alloc` feature, whose TLSF
+//! allocator frees memory, so that the memory of each proof is reused for the
+//! next. That allocator keeps no usage counts, and the platform installs it as
+//! the global allocator, so it cannot be wrapped with a counter as in the SP1
+//! program. Instead, the guest samples the top of the heap (see `heap_top`)
+//! where it holds the most data: after reading the batch, and after verifying
+//! each proof in streaming mode. Memory allocated and freed within the
+//! verification of a proof is not included, so the peak is a lower bound.
+//!
+//! The samples are taken outside the phases measured by the guest, and the
+//! cycles they take are counted so that the host can subtract them from the
+//! total.
+
+use risc0_zkvm::guest::env;
+
+pub struct HeapUsage {
+    peak: usize,
+    probe_cycles: u64,
+}
+
+impl HeapUsage {
+    pub fn new() -> Self {
+        Self {
+            peak: 0,
+            probe_cycles: 0,
+        }
+    }
+
+    /// Sample the heap usage. The bump allocator counts its usage, so this
+    /// does nothing in the default guest.
+    pub fn sample(&mut self) {
+        if cfg!(feature = "heap-embedded-alloc") {
+            let start = env::cycle_count();
+            self.peak = self.peak.max(probe::heap_top() - probe::heap_start());
+            self.probe_cycles += env::cycle_count() - start;
+        }
+    }
+
+    /// Whether `peak` is a lower bound, rather than the exact peak usage
+    pub fn is_lower_bound(&self) -> bool {
+        cfg!(feature = "heap-embedded-alloc")
+    }
+
+    /// Peak heap usage, in bytes
+    pub fn peak(&self) -> usize {
+        if cfg!(feature = "heap-embedded-alloc") {
+            self.peak
+        } else {
+            risc0_zkvm_platform::heap::used()
+        }
+    }
+
+    /// Cycles spent sampling the heap usage
+    pub fn probe_cycles(&self) -> u64 {
+        self.probe_cycles
+    }
+}
+
+mod probe {
+    use risc0_zkvm::GUEST_MAX_MEM;
+    use std::alloc::{alloc, dealloc, Layout};
+
+    /// Precision of the search made by `heap_top`, in bytes
+    const HEAP_PROBE_STEP: usize = 1 << 12;
+
+    /// Start of the heap, at the end of the ELF sections.
+    pub fn heap_start() -> usize {
+        extern "C" {
+            static _end: u8;
+        }
+        unsafe { &_end as *const u8 as usize }
+    }
+
+    /// Top of the allocated part of the heap. The heap is searched for the
+    /// largest block it can allocate. No block the guest frees comes close to
+    /// that size, so the block is the free memory above every allocated block,
+    /// and its address is the top of the heap. Each probe is smaller than the
+    /// heap, and a probe which does not fit returns null from the TLSF
+    /// allocator.
+    pub fn heap_top() -> usize {
+        // The largest size found to fit, and a size known not to
+        let (mut fits, mut too_big) = (0, GUEST_MAX_MEM - heap_start());
+        let mut top = None;
+        while too_big - fits > HEAP_PROBE_STEP {
+            let size = (fits + too_big) / 2;
+            let layout = Layout::from_size_align(size, 4).unwrap();
+            unsafe {
+                let ptr = alloc(layout);
+                if ptr.is_null() {
+                    too_big = size;
+                } else {
+                    dealloc(ptr, layout);
+                    fits = size;
+                    top = Some(ptr as usize);
+                }
+            }
+        }
+        top.expect("heap probe failed")
+    }
+}
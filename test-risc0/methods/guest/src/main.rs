@@ -1,20 +1,89 @@
+use groth16::batch::{Batch, BatchEntry};
 use groth16::has_primitive_repr::HasPrimitiveRepr;
-use groth16::{verify, Inputs, Proof, VerifyingKey};
+use groth16::input_header::InputHeader;
+use groth16::{verify_checked, VerifyingKey};
 use risc0_zkvm::guest::env;
 
+mod heap;
+
 fn main() {
-    // read the input
-    let batch_size: u32 = env::read();
-    let inputs_repr: <Inputs as HasPrimitiveRepr>::Repr = env::read();
-    let proof_repr: <Proof as HasPrimitiveRepr>::Repr = env::read();
-    let vk_repr: <VerifyingKey as HasPrimitiveRepr>::Repr = env::read();
-
-    let inputs = Inputs::from_repr(&inputs_repr);
-    let proof = Proof::from_repr(&proof_repr);
-    let vk = VerifyingKey::from_repr(&vk_repr);
-
-    for _ in 0..batch_size {
-        let result = verify(&vk, &proof, &inputs);
-        assert!(result.is_ok());
+    let mut heap = heap::HeapUsage::new();
+
+    let header: InputHeader = env::read();
+    header.check().expect("invalid guest input");
+    let mut read_cycles = 0;
+    let mut verify_cycles = 0;
+
+    // In streaming mode each proof is read and verified before the next is
+    // read. Otherwise the whole batch is read up front.
+    if header.streaming() {
+        let start = env::cycle_count();
+        let vks_repr: <Vec<VerifyingKey> as HasPrimitiveRepr>::Repr = env::read();
+        let vks = Vec::<VerifyingKey>::try_from_repr(&vks_repr)
+            .unwrap_or_else(|e| panic!("verification keys: {e}"));
+        assert_eq!(vks.len(), header.num_vks as usize);
+        check_vks(&vks);
+        read_cycles += env::cycle_count() - start;
+
+        for i in 0..header.num_proofs {
+            let start = env::cycle_count();
+            let entry_repr: <BatchEntry as HasPrimitiveRepr>::Repr = env::read();
+            let entry =
+                BatchEntry::try_from_repr(&entry_repr).unwrap_or_else(|e| panic!("proof {i}: {e}"));
+            header
+                .check_entry(&entry)
+                .unwrap_or_else(|e| panic!("proof {i}: {e}"));
+            let read_end = env::cycle_count();
+            read_cycles += read_end - start;
+
+            let vk = &vks[entry.vk_index as usize];
+            if let Err(e) = verify_checked(vk, &entry.proof, &entry.inputs) {
+                panic!("proof {i}: {e}");
+            }
+            verify_cycles += env::cycle_count() - read_end;
+            heap.sample();
+        }
+    } else {
+        let start = env::cycle_count();
+        let batch_repr: <Batch as HasPrimitiveRepr>::Repr = env::read();
+        let batch = Batch::try_from_repr(&batch_repr).unwrap_or_else(|e| panic!("batch: {e}"));
+        header.check_batch(&batch).expect("invalid guest input");
+        check_vks(&batch.vks);
+        let read_end = env::cycle_count();
+        read_cycles += read_end - start;
+        heap.sample();
+
+        for (i, entry) in batch.entries.iter().enumerate() {
+            let vk = &batch.vks[entry.vk_index as usize];
+            if let Err(e) = verify_checked(vk, &entry.proof, &entry.inputs) {
+                panic!("proof {i}: {e}");
+            }
+        }
+        verify_cycles += env::cycle_count() - read_end;
+        heap.sample();
+    }
+
+    // Report the cycles spent in each phase, the heap used (marked if it is
+    // only a lower bound), and the cycles spent measuring it, to the host
+    println!("cycle-tracker-report: read {read_cycles}");
+    println!("cycle-tracker-report: verify {verify_cycles}");
+    if heap.is_lower_bound() {
+        println!("heap-report: {} (lower bound)", heap.peak());
+    } else {
+        println!("heap-report: {}", heap.peak());
+    }
+    println!("heap-probe-report: {}", heap.probe_cycles());
+
+    // Commit to the number of proofs verified
+    env::commit(&header.num_proofs);
+}
+
+/// Check that the points of each verification key are valid. The proofs are
+/// checked as they are verified.
+fn check_vks(vks: &[VerifyingKey]) {
+    for (i, vk) in vks.iter().enumerate() {
+        if let Err(e) = vk.check() {
+            panic!("verification key {i}: {e}");
+        }
     }
 }
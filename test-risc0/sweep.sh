@@ -0,0 +1,40 @@
+#!/usr/bin/env bash
+#
+# Run the benchmark for each combination of batch size and segment limit,
+# saving the output of each run to results/ and printing a summary of the user
+# cycles, peak heap usage and proof generation time.
+#
+# Usage (from the test-risc0 directory, after `cargo build --release`):
+#   ./sweep.sh [batch sizes] [segment limit po2 values]
+#
+# e.g. ./sweep.sh "1 2 4 8" "19 20 21 22". Set MODE to choose the receipt kind
+# (default groth16).
+
+set -euo pipefail
+
+BATCH_SIZES=${1:-"1 2 4 8"}
+SEGMENT_PO2S=${2:-"19 20 21 22"}
+MODE=${MODE:-groth16}
+
+mkdir -p results _test_data
+
+for n in ${BATCH_SIZES}; do
+    for po2 in ${SEGMENT_PO2S}; do
+        log=results/risc0_batchsize_${n}_po2_${po2}.log
+        RUST_LOG=info ./target/release/host \
+            --n "${n}" --mode "${MODE}" --segment-limit-po2 "${po2}" \
+            2>&1 | tee "${log}"
+    done
+done
+
+echo
+echo "batch_size segment_limit_po2 user_cycles peak_heap_bytes proof_generation_time"
+for n in ${BATCH_SIZES}; do
+    for po2 in ${SEGMENT_PO2S}; do
+        log=results/risc0_batchsize_${n}_po2_${po2}.log
+        cycles=$(sed -n 's/^User cycles: //p' "${log}")
+        heap=$(sed -n 's/^Peak heap: \([0-9]*\) bytes$/\1/p' "${log}")
+        time=$(sed -n 's/^Proof generation time: //p' "${log}")
+        echo "${n} ${po2} ${cycles} ${heap} ${time}"
+    done
+done
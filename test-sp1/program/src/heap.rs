@@ -0,0 +1,197 @@
+//! The heap allocator of the program, which records the peak heap usage.
+//!
+//! By default this is the bump allocator installed by `sp1_zkvm::entrypoint!`,
+//! which never frees memory. In streaming mode the program calls
+//! `enable_freeing` once it has read the input header, and from then on the
+//! heap is managed by the TLSF allocator from `rlsf`, so that the memory of
+//! each proof is reused for the next. The TLSF allocator takes its memory from
+//! the bump allocator, in chunks of at least `CHUNK_SIZE` bytes as it needs
+//! them, so the heap is only limited by the memory of the zkVM.
+//!
+//! The standard library's zkVM thread-local keys assume that the allocator
+//! returns zeroed memory, as the bump allocator does, and misread the
+//! panic state otherwise. Memory from the TLSF allocator is therefore zeroed
+//! when it is allocated, since it may have been used and freed before.
+//!
+//! The SP1 executor only fills the buffer passed to `syscall_hint_read` if
+//! the program has not yet touched that memory, so the input cannot be read
+//! into memory freed by the heap. Each input buffer is instead taken from the
+//! bump allocator (see `read`), and counted as part of the heap. Once the
+//! value read from it has been deserialized, the buffer is given to the TLSF
+//! allocator in streaming mode, and otherwise kept, as the bump allocator
+//! would.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::{Cell, RefCell};
+use core::ptr::NonNull;
+use serde::de::DeserializeOwned;
+use sp1_zkvm::syscalls::{sys_alloc_aligned, syscall_hint_len, syscall_hint_read};
+
+type Tlsf = rlsf::Tlsf<'static, usize, usize, { usize::BITS as usize }, { usize::BITS as usize }>;
+
+/// Smallest block of memory the TLSF allocator takes from the bump allocator,
+/// in bytes
+const CHUNK_SIZE: usize = 1 << 20;
+
+pub struct Heap {
+    tlsf: RefCell<Tlsf>,
+    /// Start of the memory managed by the TLSF allocator, once freeing is
+    /// enabled. Memory below it was allocated by the bump allocator and is
+    /// never freed.
+    freeing_from: Cell<Option<usize>>,
+    /// Bytes allocated and not freed, including the input buffers
+    used: Cell<usize>,
+    peak: Cell<usize>,
+}
+
+// The zkVM is single-threaded, so the heap is only accessed by one thread.
+unsafe impl Sync for Heap {}
+
+impl Heap {
+    pub const fn new() -> Self {
+        Self {
+            tlsf: RefCell::new(Tlsf::new()),
+            freeing_from: Cell::new(None),
+            used: Cell::new(0),
+            peak: Cell::new(0),
+        }
+    }
+
+    fn count_alloc(&self, bytes: usize) {
+        let used = self.used.get() + bytes;
+        self.used.set(used);
+        self.peak.set(self.peak.get().max(used));
+    }
+
+    fn count_free(&self, bytes: usize) {
+        self.used.set(self.used.get() - bytes);
+    }
+
+    /// Whether `ptr` is managed by the TLSF allocator.
+    fn is_freeable(&self, ptr: *const u8) -> bool {
+        self.freeing_from
+            .get()
+            .is_some_and(|start| ptr as usize >= start)
+    }
+
+    /// Allocate from the TLSF allocator, giving it more memory from the bump
+    /// allocator if the allocation does not fit.
+    unsafe fn alloc_freeing(&self, layout: Layout) -> *mut u8 {
+        let mut tlsf = self.tlsf.borrow_mut();
+        // Room for the block headers, and for the rounding up of the size to
+        // one of the TLSF size classes
+        let mut chunk_size =
+            CHUNK_SIZE.max(layout.size() + layout.size() / 16 + layout.align() + 64);
+        let ptr = loop {
+            if let Some(ptr) = tlsf.allocate(layout) {
+                break ptr.as_ptr();
+            }
+            let chunk = NonNull::new_unchecked(sys_alloc_aligned(chunk_size, rlsf::GRANULARITY));
+            tlsf.insert_free_block_ptr(NonNull::slice_from_raw_parts(chunk, chunk_size));
+            chunk_size *= 2;
+        };
+        ptr.write_bytes(0, layout.size());
+        ptr
+    }
+
+    /// Give an input buffer which is no longer used to the TLSF allocator.
+    unsafe fn reclaim(&self, ptr: *mut u8, len: usize) {
+        self.count_free(len);
+        let block = NonNull::slice_from_raw_parts(NonNull::new_unchecked(ptr), len);
+        self.tlsf.borrow_mut().insert_free_block_ptr(block);
+    }
+}
+
+unsafe impl GlobalAlloc for Heap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = match self.freeing_from.get() {
+            Some(_) => self.alloc_freeing(layout),
+            None => sys_alloc_aligned(layout.size(), layout.align()),
+        };
+        self.count_alloc(layout.size());
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if self.is_freeable(ptr) {
+            self.tlsf
+                .borrow_mut()
+                .deallocate(NonNull::new_unchecked(ptr), layout.align());
+            self.count_free(layout.size());
+        }
+    }
+}
+
+/// Manage the memory allocated from now on with the TLSF allocator, which
+/// frees memory.
+pub fn enable_freeing() {
+    let heap = &crate::HEAP;
+    if heap.freeing_from.get().is_none() {
+        heap.freeing_from.set(Some(bump_top()));
+    }
+}
+
+/// Peak heap usage so far, in bytes, including the input buffers live at the
+/// time.
+pub fn peak() -> usize {
+    crate::HEAP.peak.get()
+}
+
+/// Memory taken from the zkVM by the heap so far, in bytes, including the
+/// input buffers and the memory held by the TLSF allocator.
+pub fn reserved() -> usize {
+    extern "C" {
+        // End of the ELF sections, where the heap starts
+        static _end: u8;
+    }
+    bump_top() - unsafe { &_end as *const u8 as usize }
+}
+
+/// Top of the memory allocated by the bump allocator.
+fn bump_top() -> usize {
+    // An empty allocation returns the next free address without moving it
+    unsafe { sys_alloc_aligned(0, 1) as usize }
+}
+
+/// The serialized form of one input value, counted in the heap usage until
+/// it is dropped.
+struct InputBuffer {
+    ptr: *mut u8,
+    len: usize,
+    capacity: usize,
+}
+
+impl InputBuffer {
+    /// Read the next value of the input stream into fresh memory.
+    fn read() -> Self {
+        let len = syscall_hint_len();
+        // Whole words, as the hint is written a word at a time
+        let capacity = len.div_ceil(4) * 4;
+        unsafe {
+            let ptr = sys_alloc_aligned(capacity, 4);
+            syscall_hint_read(ptr, len);
+            crate::HEAP.count_alloc(capacity);
+            Self { ptr, len, capacity }
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl Drop for InputBuffer {
+    fn drop(&mut self) {
+        let heap = &crate::HEAP;
+        if heap.is_freeable(self.ptr) {
+            unsafe { heap.reclaim(self.ptr, self.capacity) }
+        }
+    }
+}
+
+/// Read a value from the input stream, as `sp1_zkvm::io::read` does. The
+/// serialized value is dropped once it has been deserialized.
+pub fn read<T: DeserializeOwned>() -> T {
+    let buf = InputBuffer::read();
+    bincode::deserialize(buf.as_slice()).expect("deserialization failed")
+}
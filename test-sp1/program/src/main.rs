@@ -1,25 +1,122 @@
 //! Naive Groth16 Aggregation using SP1
 #![no_main]
-sp1_zkvm::entrypoint!(main);
 
+mod heap;
+
+// As `sp1_zkvm::entrypoint!(main)`, but with a heap allocator which records
+// the peak heap usage, and frees memory in streaming mode (see `heap`).
+#[global_allocator]
+static HEAP: heap::Heap = heap::Heap::new();
+
+mod zkvm_generated_main {
+    #[no_mangle]
+    fn main() {
+        super::main()
+    }
+}
+
+use groth16::batch::{Batch, BatchEntry};
 use groth16::has_primitive_repr::HasPrimitiveRepr;
-use groth16::{verify, Inputs, Proof, VerifyingKey};
+use groth16::input_header::InputHeader;
+use groth16::{verify_checked, VerifyingKey};
+
+/// File descriptor on which the reason for rejecting the input is written
+/// before panicking, since the host only sees the exit code of a panic.
+/// Matches `FD_ERROR` in the script.
+const FD_ERROR: u32 = 100;
+
+/// File descriptor on which the peak heap usage is written (as a
+/// little-endian u64). Matches `FD_HEAP_REPORT` in the script.
+const FD_HEAP_REPORT: u32 = 101;
 
 pub fn main() {
-    let batch_size: u32 = sp1_zkvm::io::read();
+    let header: InputHeader = heap::read();
+    header
+        .check()
+        .unwrap_or_else(|e| fail(format!("invalid program input: {e}")));
+
+    // In streaming mode each proof is read and verified before the next is
+    // read. Otherwise the whole batch is read up front.
+    if header.streaming() {
+        // Reuse the memory of each proof for the next
+        heap::enable_freeing();
 
-    let inputs_repr: <Inputs as HasPrimitiveRepr>::Repr = sp1_zkvm::io::read();
-    let proof_repr: <Proof as HasPrimitiveRepr>::Repr = sp1_zkvm::io::read();
-    let vk_repr: <VerifyingKey as HasPrimitiveRepr>::Repr = sp1_zkvm::io::read();
+        println!("cycle-tracker-report-start: read");
+        let vks_repr: <Vec<VerifyingKey> as HasPrimitiveRepr>::Repr = heap::read();
+        let vks = Vec::<VerifyingKey>::try_from_repr(&vks_repr)
+            .unwrap_or_else(|e| fail(format!("verification keys: {e}")));
+        if vks.len() != header.num_vks as usize {
+            fail(format!(
+                "invalid program input: expected {} verification keys, got {}",
+                header.num_vks,
+                vks.len()
+            ));
+        }
+        check_vks(&vks);
+        println!("cycle-tracker-report-end: read");
 
-    let inputs = Inputs::from_repr(&inputs_repr);
-    let proof = Proof::from_repr(&proof_repr);
-    let vk = VerifyingKey::from_repr(&vk_repr);
+        for i in 0..header.num_proofs {
+            println!("cycle-tracker-report-start: read");
+            let entry_repr: <BatchEntry as HasPrimitiveRepr>::Repr = heap::read();
+            let entry = BatchEntry::try_from_repr(&entry_repr)
+                .unwrap_or_else(|e| fail(format!("proof {i}: {e}")));
+            header
+                .check_entry(&entry)
+                .unwrap_or_else(|e| fail(format!("proof {i}: {e}")));
+            println!("cycle-tracker-report-end: read");
 
-    // For simplicity, we simulate verifying a batch of proofs by repeatedly
-    // verifying one proof.
-    for _ in 0..batch_size {
-        let result = verify(&vk, &proof, &inputs);
-        assert!(result.is_ok());
+            println!("cycle-tracker-report-start: verify");
+            let vk = &vks[entry.vk_index as usize];
+            if let Err(e) = verify_checked(vk, &entry.proof, &entry.inputs) {
+                fail(format!("proof {i}: {e}"));
+            }
+            println!("cycle-tracker-report-end: verify");
+        }
+    } else {
+        println!("cycle-tracker-report-start: read");
+        let batch_repr: <Batch as HasPrimitiveRepr>::Repr = heap::read();
+        let batch =
+            Batch::try_from_repr(&batch_repr).unwrap_or_else(|e| fail(format!("batch: {e}")));
+        header
+            .check_batch(&batch)
+            .unwrap_or_else(|e| fail(format!("invalid program input: {e}")));
+        check_vks(&batch.vks);
+        println!("cycle-tracker-report-end: read");
+
+        // Verify each proof in turn against its verification key.
+        println!("cycle-tracker-report-start: verify");
+        for (i, entry) in batch.entries.iter().enumerate() {
+            let vk = &batch.vks[entry.vk_index as usize];
+            if let Err(e) = verify_checked(vk, &entry.proof, &entry.inputs) {
+                fail(format!("proof {i}: {e}"));
+            }
+        }
+        println!("cycle-tracker-report-end: verify");
+    }
+
+    // Report the peak heap usage, and the memory taken by the heap, to the
+    // host
+    let peak = heap::peak();
+    println!("heap-report: {peak}");
+    println!("heap-reserved-report: {}", heap::reserved());
+    sp1_zkvm::io::write(FD_HEAP_REPORT, &(peak as u64).to_le_bytes());
+
+    // Commit to the number of proofs verified
+    sp1_zkvm::io::commit(&header.num_proofs);
+}
+
+/// Check that the points of each verification key are valid. The proofs are
+/// checked as they are verified.
+fn check_vks(vks: &[VerifyingKey]) {
+    for (i, vk) in vks.iter().enumerate() {
+        if let Err(e) = vk.check() {
+            fail(format!("verification key {i}: {e}"));
+        }
     }
 }
+
+/// Report why the input was rejected to the host, and panic.
+fn fail(msg: String) -> ! {
+    sp1_zkvm::io::write(FD_ERROR, msg.as_bytes());
+    panic!("{msg}");
+}
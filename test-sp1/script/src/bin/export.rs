@@ -0,0 +1,94 @@
+//! Export a saved PLONK or Groth16 aggregation proof as calldata for the SP1
+//! verifier contract (`ISP1Verifier.verifyProof`).
+//!
+//! ```sh
+//! ./target/release/export --proof ../_test_data/sp1_agg_proof_batch_size_1.plonk
+//! ```
+use alloy_primitives::B256;
+use alloy_sol_types::{sol, SolCall};
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use serde::Serialize;
+use sp1_groth16_aggregation_script::{load_proof, G16_AGGREGATION_ELF};
+use sp1_sdk::{HashableKey, ProverClient, SP1Proof};
+use std::path::PathBuf;
+
+sol! {
+    interface ISP1Verifier {
+        function verifyProof(bytes32 programVKey, bytes calldata publicValues, bytes calldata proofBytes) external view;
+    }
+}
+
+/// The arguments for the export command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct ExportArgs {
+    /// PLONK or Groth16 proof file, as written by the prove command
+    #[clap(long)]
+    proof: PathBuf,
+
+    /// Output file (defaults to the proof file with a `.calldata.json`
+    /// extension)
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+/// Hex-encoded (0x-prefixed) arguments and calldata for `verifyProof`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CalldataJson {
+    function: String,
+    program_vkey: String,
+    public_values: String,
+    proof_bytes: String,
+    calldata: String,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn main() -> Result<()> {
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+
+    let args = ExportArgs::parse();
+
+    let proof = load_proof(&args.proof)?;
+    if !matches!(proof.proof, SP1Proof::Plonk(_) | SP1Proof::Groth16(_)) {
+        bail!("proof must be produced with --mode plonk or --mode groth16");
+    }
+
+    let client = ProverClient::new();
+    let (_, vk) = client.setup(G16_AGGREGATION_ELF);
+    client
+        .verify(&proof, &vk)
+        .context("failed to verify proof")?;
+
+    let program_vkey = hex::decode(vk.bytes32().trim_start_matches("0x"))?;
+    let public_values = proof.public_values.to_vec();
+    // Prefixed with the verifier selector expected by the SP1 verifier gateway
+    let proof_bytes = proof.bytes();
+
+    let call = ISP1Verifier::verifyProofCall {
+        programVKey: B256::from_slice(&program_vkey),
+        publicValues: public_values.clone().into(),
+        proofBytes: proof_bytes.clone().into(),
+    };
+    let calldata = call.abi_encode();
+
+    let json = CalldataJson {
+        function: ISP1Verifier::verifyProofCall::SIGNATURE.to_string(),
+        program_vkey: to_hex(&program_vkey),
+        public_values: to_hex(&public_values),
+        proof_bytes: to_hex(&proof_bytes),
+        calldata: to_hex(&calldata),
+    };
+    let output = args
+        .output
+        .unwrap_or_else(|| args.proof.with_extension("calldata.json"));
+    std::fs::write(&output, serde_json::to_string_pretty(&json)?)
+        .with_context(|| format!("failed to write {}", output.display()))?;
+    println!("Calldata saved to file: {}", output.display());
+    Ok(())
+}
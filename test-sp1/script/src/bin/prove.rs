@@ -1,16 +1,25 @@
 use clap::Parser;
+use groth16::batch::Batch;
 use groth16::has_json_repr::load_json;
-use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::sample::{SAMPLE_INPUTS_FILE, SAMPLE_PROOF_FILE, SAMPLE_VK_FILE};
 use groth16::{Inputs, Proof, VerifyingKey};
-use sp1_sdk::{ProverClient, SP1Stdin};
+use sp1_core_executor::{Executor, Program};
+use sp1_groth16_aggregation_script::profile::{fold_trace, write_flamegraph};
+use sp1_groth16_aggregation_script::stages::{prove_stages, Mode};
+use sp1_groth16_aggregation_script::{execute, write_program_input, G16_AGGREGATION_ELF};
+use sp1_sdk::{HashableKey, ProverClient, SP1Stdin};
+use sp1_stark::SP1ProverOpts;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
 use std::time::Instant;
 
-pub const G16_AGGREGATION_ELF: &[u8] =
-    include_bytes!("../../../program/elf/riscv32im-succinct-zkvm-elf");
-
-const SAMPLE_INPUTS_FILE: &str = "../../groth16/src/data/inputs.json";
-const SAMPLE_PROOF_FILE: &str = "../../groth16/src/data/proof.json";
-const SAMPLE_VK_FILE: &str = "../../groth16/src/data/vk.json";
+/// Default directory for profiles and logs, in `test-sp1`
+const RESULTS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../results");
+/// Default directory for proofs, in `test-sp1`
+const TEST_DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../_test_data");
 
 /// The arguments for the prove command.
 #[derive(Parser, Debug)]
@@ -19,45 +28,235 @@ struct ProveArgs {
     /// Batch size
     #[clap(long, default_value = "1")]
     n: u32,
+
+    /// Verification key JSON file
+    #[clap(long, default_value = SAMPLE_VK_FILE)]
+    vk: String,
+
+    /// Proof JSON file, verified `n` times
+    #[clap(long, default_value = SAMPLE_PROOF_FILE)]
+    proof: String,
+
+    /// Public inputs JSON file
+    #[clap(long, default_value = SAMPLE_INPUTS_FILE)]
+    inputs: String,
+
+    /// Re-randomise each copy of the proof, so that the batch contains `n`
+    /// distinct proofs
+    #[clap(long)]
+    rerandomize: bool,
+
+    /// Seed for the re-randomisation scalars
+    #[clap(long, default_value_t = 0, requires = "rerandomize")]
+    seed: u64,
+
+    /// Send the proofs to the guest one at a time, verifying each before the
+    /// next is read, instead of reading the whole batch up front
+    #[clap(long)]
+    streaming: bool,
+
+    /// JSON Lines file of application proofs to verify, instead of repeating
+    /// a single proof
+    #[clap(
+        long,
+        requires = "vk_table",
+        conflicts_with_all = ["n", "vk", "proof", "inputs", "rerandomize"]
+    )]
+    batch: Option<String>,
+
+    /// JSON table of the verification keys referred to by the `--batch`
+    /// proofs
+    #[clap(long)]
+    vk_table: Option<String>,
+
+    /// Kind of proof to produce
+    #[clap(long, value_enum, default_value = "plonk")]
+    mode: Mode,
+
+    /// Number of cycles per shard (SDK default if unset)
+    #[clap(long)]
+    shard_size: Option<usize>,
+
+    /// Number of shards committed to in each batch (SDK default if unset)
+    #[clap(long)]
+    shard_batch_size: Option<usize>,
+
+    /// Execute with tracing enabled and write a flamegraph of the guest to
+    /// the profile directory, instead of generating a proof
+    #[clap(long)]
+    profile: bool,
+
+    /// Directory in which to save the flamegraph
+    #[clap(long, default_value = RESULTS_DIR)]
+    profile_dir: PathBuf,
+
+    /// Sample the guest call stack once every this many cycles when profiling
+    #[clap(long, default_value = "100", value_parser = clap::value_parser!(u64).range(1..))]
+    sample_rate: u64,
+
+    /// Directory in which to save the proof and program verification key
+    #[clap(long, default_value = TEST_DATA_DIR)]
+    output_dir: PathBuf,
 }
 
 fn main() {
     // Setup the logger.
     sp1_sdk::utils::setup_logger();
 
-    // Read input from JSON
-    let inputs: Inputs = load_json(SAMPLE_INPUTS_FILE);
-    let proof: Proof = load_json(SAMPLE_PROOF_FILE);
-    let groth16_vk: VerifyingKey = load_json(SAMPLE_VK_FILE);
-
-    // Parse batch size from command line
     let args = ProveArgs::parse();
-    let batch_size: u32 = args.n;
+
+    // Read the batch from JSON
+    let batch = match &args.batch {
+        Some(proofs_file) => Batch::load(proofs_file, args.vk_table.as_ref().unwrap()).unwrap(),
+        None => {
+            let inputs: Inputs = load_json(&args.inputs);
+            let proof: Proof = load_json(&args.proof);
+            let groth16_vk: VerifyingKey = load_json(&args.vk);
+            if args.rerandomize {
+                Batch::rerandomized(groth16_vk, inputs, proof, args.n, args.seed)
+            } else {
+                Batch::repeated(groth16_vk, inputs, proof, args.n)
+            }
+        }
+    };
+    let batch_size = batch.len() as u32;
     println!("Batch size: {batch_size}");
+    println!("Verification keys: {}", batch.vks.len());
+    println!("Streaming input: {}", args.streaming);
+
+    // Write the verification keys, proofs and inputs to stdin.
+    let stdin = write_program_input(&batch, args.streaming);
 
-    // Write the batch size, inputs, proof, and vk to stdin.
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&batch_size);
-    stdin.write(&inputs.to_repr());
-    stdin.write(&proof.to_repr());
-    stdin.write(&groth16_vk.to_repr());
-    println!("Public input length: {}", inputs.len());
+    // Shard options, overriding the SDK defaults
+    let mut opts = SP1ProverOpts::default();
+    if let Some(shard_size) = args.shard_size {
+        opts.core_opts.shard_size = shard_size;
+    }
+    if let Some(shard_batch_size) = args.shard_batch_size {
+        opts.core_opts.shard_batch_size = shard_batch_size;
+    }
+    println!("Shard size: {}", opts.core_opts.shard_size);
+    println!("Shard batch size: {}", opts.core_opts.shard_batch_size);
 
     // Setup the prover client.
     let client = ProverClient::new();
 
+    if args.profile {
+        run_profiler(stdin, opts, batch_size, args.sample_rate, &args.profile_dir);
+        return;
+    }
+
+    // Execute the program first, to report its cycles and memory use.
+    let (report, peak_heap) = execute(&client, stdin.clone()).expect("failed to execute program");
+    println!("Total cycles: {}", report.total_instruction_count());
+    let mut phases: Vec<_> = report.cycle_tracker.iter().collect();
+    phases.sort();
+    for (phase, cycles) in phases {
+        println!("Cycles {phase}: {cycles}");
+    }
+    println!("Peak heap: {peak_heap} bytes");
+
     // Setup the program.
     let (pk, vk) = client.setup(G16_AGGREGATION_ELF);
 
-    // Generate the proof.
+    // Generate the proof, timing each stage.
+    println!("Proof mode: {:?}", args.mode);
     let now = Instant::now();
-    let proof = client
-        .prove(&pk, stdin)
-        .plonk()
-        .run()
-        .expect("failed to generate proof");
-    println!("Proof generation time: {}s", now.elapsed().as_secs());
+    let proof = prove_stages(&client, &pk, stdin, opts, args.mode);
+    println!("Proof generation time: {:.2}s", now.elapsed().as_secs_f64());
+    println!(
+        "Proof size: {} bytes",
+        bincode::serialize(&proof.proof).unwrap().len()
+    );
 
     // Verify the proof.
     client.verify(&proof, &vk).expect("failed to verify proof");
+
+    // Save the proof (including public values) and the program verification
+    // key, so they can be verified again later.
+    std::fs::create_dir_all(&args.output_dir).expect("failed to create output directory");
+    let proof_file = args.output_dir.join(format!(
+        "sp1_agg_proof_batch_size_{}.{}",
+        batch_size,
+        args.mode.extension()
+    ));
+    proof.save(&proof_file).expect("failed to save proof");
+    println!("Proof saved to file: {}", proof_file.display());
+
+    let public_values_file = args.output_dir.join(format!(
+        "sp1_agg_public_values_batch_size_{}.bin",
+        batch_size
+    ));
+    std::fs::write(&public_values_file, proof.public_values.to_vec())
+        .expect("failed to save public values");
+    println!(
+        "Public values saved to file: {}",
+        public_values_file.display()
+    );
+
+    let vk_file = args.output_dir.join("sp1_agg_vkey.bin");
+    std::fs::write(&vk_file, bincode::serialize(&vk).unwrap()).expect("failed to save vkey");
+    println!("Program verification key: {}", vk.bytes32());
+    println!(
+        "Program verification key saved to file: {}",
+        vk_file.display()
+    );
+}
+
+/// Execute the guest, recording a pc trace, and render the sampled call stacks
+/// as `<profile_dir>/sp1_batchsize_<n>_flamegraph.svg`.
+fn run_profiler(
+    stdin: SP1Stdin,
+    opts: SP1ProverOpts,
+    batch_size: u32,
+    sample_rate: u64,
+    profile_dir: &Path,
+) {
+    std::fs::create_dir_all(profile_dir).expect("failed to create profile directory");
+    let trace_pipe = profile_dir.join(format!("sp1_batchsize_{batch_size}.trace"));
+    let svg_file = profile_dir.join(format!("sp1_batchsize_{batch_size}_flamegraph.svg"));
+
+    // The trace is written to a named pipe and folded as it is written, since
+    // it takes 4 bytes per cycle.
+    let _ = std::fs::remove_file(&trace_pipe);
+    let status = Command::new("mkfifo")
+        .arg(&trace_pipe)
+        .status()
+        .expect("failed to run mkfifo");
+    assert!(status.success(), "failed to create trace pipe");
+    let reader_pipe = trace_pipe.clone();
+    let folder = thread::spawn(move || {
+        fold_trace(G16_AGGREGATION_ELF, File::open(reader_pipe)?, sample_rate)
+    });
+
+    // Execute as `ProverClient::execute` does, with the executor writing the
+    // pc of every cycle to its trace buffer. Opening the pipe waits for the
+    // reader.
+    let program = Program::from(G16_AGGREGATION_ELF).expect("failed to load program");
+    let mut runtime = Executor::new(program, opts.core_opts);
+    let file = File::options()
+        .write(true)
+        .open(&trace_pipe)
+        .expect("failed to open trace pipe");
+    runtime.trace_buf = Some(BufWriter::new(file));
+    runtime.write_vecs(&stdin.buffer);
+    let now = Instant::now();
+    runtime.run_fast().expect("failed to execute");
+    println!("Execution time: {:.2}s", now.elapsed().as_secs_f64());
+    println!("Total cycles: {}", runtime.report.total_instruction_count());
+    // Close the pipe, ending the trace
+    runtime.trace_buf = None;
+
+    let folded = folder
+        .join()
+        .expect("trace folding panicked")
+        .expect("failed to fold trace");
+    std::fs::remove_file(&trace_pipe).expect("failed to remove trace pipe");
+    write_flamegraph(
+        &folded,
+        &format!("SP1 Groth16 aggregation, batch size {batch_size}"),
+        &svg_file,
+    )
+    .expect("failed to write flamegraph");
+    println!("Flamegraph saved to file: {}", svg_file.display());
 }
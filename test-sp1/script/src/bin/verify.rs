@@ -0,0 +1,88 @@
+//! Verify a saved aggregation proof against the program verification key.
+//! The key is derived from the ELF, and checked against the key saved by the
+//! prove command, `sp1_agg_vkey.bin` next to the proof (or `--vkey <file>`),
+//! if there is one.
+//!
+//! ```sh
+//! ./target/release/verify --proof ../_test_data/sp1_agg_proof_batch_size_1.plonk
+//! ```
+use anyhow::Context;
+use clap::Parser;
+use sp1_groth16_aggregation_script::{load_proof, G16_AGGREGATION_ELF};
+use sp1_sdk::{HashableKey, ProverClient, SP1VerifyingKey};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+/// The arguments for the verify command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct VerifyArgs {
+    /// Proof file, as written by the prove command
+    #[clap(long)]
+    proof: PathBuf,
+
+    /// Program verification key saved by the prove command (defaults to
+    /// `sp1_agg_vkey.bin` next to the proof, if it exists)
+    #[clap(long)]
+    vkey: Option<PathBuf>,
+}
+
+/// Load a program verification key saved by the prove command.
+fn load_vkey(path: &Path) -> anyhow::Result<SP1VerifyingKey> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    bincode::deserialize(&bytes).with_context(|| format!("failed to decode {}", path.display()))
+}
+
+fn main() {
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+
+    let args = VerifyArgs::parse();
+
+    let mut proof = load_proof(&args.proof).unwrap_or_else(|e| {
+        eprintln!("Failed to load proof: {e:#}");
+        exit(1)
+    });
+
+    // Derive the program verification key from the ELF, rather than trusting
+    // a saved key.
+    let client = ProverClient::new();
+    let (_, vk) = client.setup(G16_AGGREGATION_ELF);
+    println!("Program verification key: {}", vk.bytes32());
+
+    // The key saved with the proof must be that of the same program.
+    let vkey_file = args
+        .vkey
+        .clone()
+        .unwrap_or_else(|| args.proof.with_file_name("sp1_agg_vkey.bin"));
+    if args.vkey.is_some() || vkey_file.exists() {
+        let saved_vk = load_vkey(&vkey_file).unwrap_or_else(|e| {
+            eprintln!("Failed to load program verification key: {e:#}");
+            exit(1)
+        });
+        if saved_vk.bytes32() != vk.bytes32() {
+            eprintln!(
+                "Saved program verification key {} ({}) does not match the ELF",
+                vkey_file.display(),
+                saved_vk.bytes32()
+            );
+            exit(1)
+        }
+        println!("Matches saved key: {}", vkey_file.display());
+    }
+
+    if let Err(e) = client.verify(&proof, &vk) {
+        eprintln!("Proof verification failed: {e}");
+        exit(1)
+    }
+    println!("Proof verified");
+
+    // Proofs from older programs commit nothing.
+    if proof.public_values.as_slice().is_empty() {
+        println!("No committed outputs");
+        return;
+    }
+    let batch_size: u32 = proof.public_values.read();
+    println!("Batch size: {batch_size}");
+}
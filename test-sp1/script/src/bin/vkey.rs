@@ -2,16 +2,12 @@
 //!
 //! You can run this script using the following command:
 //! ```shell
-//! RUST_LOG=info cargo run --package fibonacci-script --bin vkey --release
+//! RUST_LOG=info cargo run --package sp1-groth16-aggregation-script --bin vkey --release
 //! ```
 
+use sp1_groth16_aggregation_script::G16_AGGREGATION_ELF;
 use sp1_sdk::{HashableKey, ProverClient};
 
-/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
-///
-/// This file is generated by running `cargo prove build` inside the `program` directory.
-pub const FIBONACCI_ELF: &[u8] = include_bytes!("../../../program/elf/riscv32im-succinct-zkvm-elf");
-
 fn main() {
     // Setup the logger.
     sp1_sdk::utils::setup_logger();
@@ -20,7 +16,7 @@ fn main() {
     let client = ProverClient::new();
 
     // Setup the program.
-    let (_, vk) = client.setup(FIBONACCI_ELF);
+    let (_, vk) = client.setup(G16_AGGREGATION_ELF);
 
     // Print the verification key.
     println!("Program Verification Key: {}", vk.bytes32());
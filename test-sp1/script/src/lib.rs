@@ -0,0 +1,74 @@
+use anyhow::Context;
+use groth16::batch::Batch;
+use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::input_header::InputHeader;
+use sp1_sdk::{ExecutionReport, ProverClient, SP1ProofWithPublicValues, SP1Stdin};
+use std::fs::File;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+pub mod profile;
+pub mod stages;
+
+/// The ELF of the aggregation program, generated by `cargo prove build` inside
+/// the `program` directory.
+pub const G16_AGGREGATION_ELF: &[u8] =
+    include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+/// File descriptor on which the program writes the reason for rejecting its
+/// input before panicking. Register a hook for it with `with_hook` to read the
+/// reason.
+pub const FD_ERROR: u32 = 100;
+
+/// File descriptor on which the program writes its peak heap usage in bytes
+/// (as a little-endian u64) when it finishes.
+pub const FD_HEAP_REPORT: u32 = 101;
+
+/// Execute the program without proving, returning its execution report (with
+/// the total cycles and the cycles of each phase marked with the cycle
+/// tracker) and the peak heap usage it reports, in bytes.
+pub fn execute(client: &ProverClient, stdin: SP1Stdin) -> anyhow::Result<(ExecutionReport, u64)> {
+    let peak_heap = Arc::new(Mutex::new(None));
+    let hook_peak_heap = peak_heap.clone();
+    let (_, report) = client
+        .execute(G16_AGGREGATION_ELF, stdin)
+        .with_hook(FD_HEAP_REPORT, move |_, buf| {
+            *hook_peak_heap.lock().unwrap() = Some(u64::from_le_bytes(buf.try_into().unwrap()));
+            vec![]
+        })
+        .run()?;
+    let peak_heap = peak_heap
+        .lock()
+        .unwrap()
+        .ok_or_else(|| anyhow::Error::msg("no heap report"))?;
+    Ok((report, peak_heap))
+}
+
+/// Load a proof saved by the prove command. This reads the file as
+/// `SP1ProofWithPublicValues::load` does, which panics if the file cannot be
+/// opened, and instead returns an error naming the file.
+pub fn load_proof(path: &Path) -> anyhow::Result<SP1ProofWithPublicValues> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    bincode::deserialize_from(file).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// Write the program input: an `InputHeader`, followed by the verification
+/// keys of the batch and the proofs to be verified (each with its inputs and
+/// the index of its key).
+///
+/// In streaming mode the keys are written first and then each proof
+/// separately, so that the program can verify each proof before reading the
+/// next. Otherwise the batch is written as a whole.
+pub fn write_program_input(batch: &Batch, streaming: bool) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&InputHeader::new(batch, streaming));
+    if streaming {
+        stdin.write(&batch.vks.to_repr());
+        for entry in &batch.entries {
+            stdin.write(&entry.to_repr());
+        }
+    } else {
+        stdin.write(&batch.to_repr());
+    }
+    stdin
+}
@@ -0,0 +1,146 @@
+//! Render flamegraphs from SP1 execution traces.
+//!
+//! When given a trace buffer, the SP1 executor writes the program counter of
+//! every executed instruction to it (as big-endian `u32`s). That is 4 bytes per
+//! cycle, several GB for the larger batches, so the trace is not stored: the
+//! buffer is a named pipe, and the trace is folded as it is written (see
+//! `fold_trace`). We map each pc to a function using the symbol table of the
+//! guest ELF, reconstruct the call stack as execution enters and leaves
+//! functions, and sample it every `sample_rate` cycles.
+
+use goblin::elf::{sym::STT_FUNC, Elf};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Result};
+use std::path::Path;
+
+/// A function symbol from the guest ELF.
+struct Function {
+    start: u32,
+    end: u32,
+    name: String,
+}
+
+/// Function symbols of the guest ELF, sorted by start address.
+struct Symbols {
+    functions: Vec<Function>,
+    starts: HashMap<u32, usize>,
+}
+
+impl Symbols {
+    fn from_elf(elf_bytes: &[u8]) -> Result<Self> {
+        let elf = Elf::parse(elf_bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        let mut functions: Vec<Function> = elf
+            .syms
+            .iter()
+            .filter(|sym| sym.st_type() == STT_FUNC && sym.st_size > 0)
+            .map(|sym| {
+                let name = elf.strtab.get_at(sym.st_name).unwrap_or("<unknown>");
+                Function {
+                    start: sym.st_value as u32,
+                    end: (sym.st_value + sym.st_size) as u32,
+                    // `;` separates frames in the folded stack format
+                    name: format!("{:#}", rustc_demangle::demangle(name)).replace(';', ","),
+                }
+            })
+            .collect();
+        functions.sort_by_key(|f| f.start);
+        let starts = functions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.start, i))
+            .collect();
+        Ok(Self { functions, starts })
+    }
+
+    /// Index of the function containing `pc`, if any.
+    fn lookup(&self, pc: u32) -> Option<usize> {
+        let i = self.functions.partition_point(|f| f.start <= pc);
+        (i > 0 && pc < self.functions[i - 1].end).then(|| i - 1)
+    }
+
+    fn contains(&self, idx: usize, pc: u32) -> bool {
+        let f = &self.functions[idx];
+        f.start <= pc && pc < f.end
+    }
+}
+
+/// Read a pc trace until the end of the stream, sample the guest call stack
+/// every `sample_rate` cycles and return the stacks in folded format (one
+/// `frame;frame;frame count` line per distinct stack). Only the sample counts
+/// are kept, so the trace can be read from a pipe as it is written.
+pub fn fold_trace(elf_bytes: &[u8], trace: impl Read, sample_rate: u64) -> Result<Vec<String>> {
+    let symbols = Symbols::from_elf(elf_bytes)?;
+    let mut reader = BufReader::new(trace);
+
+    let mut stack: Vec<usize> = Vec::new();
+    let mut samples: HashMap<Vec<usize>, u64> = HashMap::new();
+    let mut cycle: u64 = 0;
+    let mut prev_pc: Option<u32> = None;
+    let mut buf = [0u8; 4];
+    loop {
+        match reader.read_exact(&mut buf) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        let pc = u32::from_be_bytes(buf);
+
+        // A jump to the start of a function is a call unless it comes from
+        // within the function (e.g. a loop back to its first instruction).
+        let call = symbols
+            .starts
+            .get(&pc)
+            .filter(|&&idx| !prev_pc.is_some_and(|prev| symbols.contains(idx, prev)));
+        prev_pc = Some(pc);
+        if let Some(&idx) = call {
+            // Entering a function
+            stack.push(idx);
+        } else {
+            // Unwind any functions we have returned from
+            while let Some(&top) = stack.last() {
+                if symbols.contains(top, pc) {
+                    break;
+                }
+                stack.pop();
+            }
+            // Jumped somewhere we weren't tracking (e.g. a tail call)
+            if stack.is_empty() {
+                if let Some(idx) = symbols.lookup(pc) {
+                    stack.push(idx);
+                }
+            }
+        }
+
+        if cycle % sample_rate == 0 {
+            *samples.entry(stack.clone()).or_default() += 1;
+        }
+        cycle += 1;
+    }
+
+    Ok(samples
+        .into_iter()
+        .map(|(stack, count)| {
+            let frames: Vec<&str> = stack
+                .iter()
+                .map(|&idx| symbols.functions[idx].name.as_str())
+                .collect();
+            let frames = if frames.is_empty() {
+                "<unknown>".to_string()
+            } else {
+                frames.join(";")
+            };
+            format!("{frames} {count}")
+        })
+        .collect())
+}
+
+/// Render folded stacks as a flamegraph SVG at `svg_path`.
+pub fn write_flamegraph(folded: &[String], title: &str, svg_path: &Path) -> Result<()> {
+    let mut options = inferno::flamegraph::Options::default();
+    options.title = title.to_string();
+    options.count_name = "samples".to_string();
+    let writer = BufWriter::new(File::create(svg_path)?);
+    inferno::flamegraph::from_lines(&mut options, folded.iter().map(String::as_str), writer)
+        .map_err(Error::other)
+}
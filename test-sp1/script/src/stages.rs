@@ -0,0 +1,135 @@
+//! The proving pipeline of the SP1 SDK, run one stage at a time so that each
+//! stage can be timed.
+//!
+//! `prove_stages` mirrors `CpuProver::prove` in sp1-sdk 1.2.0, the version in
+//! `Cargo.toml`, calling the same `SP1Prover` stages in the same order, and
+//! building the circuit artifacts in the same way in SP1 dev mode. It must be
+//! updated along with the SDK.
+
+use clap::ValueEnum;
+use sp1_prover::build::{
+    sp1_dev_mode, try_build_groth16_bn254_artifacts_dev, try_build_plonk_bn254_artifacts_dev,
+};
+use sp1_sdk::install::try_install_circuit_artifacts;
+use sp1_sdk::{
+    ProverClient, SP1Context, SP1Proof, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin,
+};
+use sp1_stark::SP1ProverOpts;
+use std::time::Instant;
+
+/// The kind of proof to produce. Each mode includes the stages of the
+/// previous one.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, ValueEnum)]
+pub enum Mode {
+    /// One STARK per shard
+    Core,
+    /// Shard STARKs recursively compressed into a single STARK
+    Compressed,
+    /// Compressed STARK wrapped in a PLONK proof, for on-chain verification
+    Plonk,
+    /// Compressed STARK wrapped in a Groth16 proof, for on-chain verification
+    Groth16,
+}
+
+impl Mode {
+    /// Extension of the saved proof file
+    pub fn extension(self) -> &'static str {
+        match self {
+            Mode::Core => "core",
+            Mode::Compressed => "compressed",
+            Mode::Plonk => "plonk",
+            Mode::Groth16 => "g16",
+        }
+    }
+}
+
+/// Generate a proof by running the prover stages up to `mode` one at a time,
+/// as `ProverClient::prove` does, and report the time taken by each stage and
+/// the size of its proof.
+pub fn prove_stages(
+    client: &ProverClient,
+    pk: &SP1ProvingKey,
+    stdin: SP1Stdin,
+    opts: SP1ProverOpts,
+    mode: Mode,
+) -> SP1ProofWithPublicValues {
+    let prover = client.prover.sp1_prover();
+    let sp1_version = client.prover.version().to_string();
+
+    // One STARK per shard
+    let stage = Instant::now();
+    let core_proof = prover
+        .prove_core(pk, &stdin, opts, SP1Context::default())
+        .expect("failed to generate core proof");
+    print_stage("core", stage, &core_proof.proof);
+    let public_values = core_proof.public_values.clone();
+    if mode == Mode::Core {
+        return SP1ProofWithPublicValues {
+            proof: SP1Proof::Core(core_proof.proof.0),
+            stdin,
+            public_values,
+            sp1_version,
+        };
+    }
+
+    // Recursively compressed into a single STARK
+    let stage = Instant::now();
+    let deferred_proofs = stdin.proofs.iter().map(|p| p.0.clone()).collect();
+    let compressed = prover
+        .compress(&pk.vk, core_proof, deferred_proofs, opts)
+        .expect("failed to compress proof");
+    print_stage("compress", stage, &compressed);
+    if mode == Mode::Compressed {
+        return SP1ProofWithPublicValues {
+            proof: SP1Proof::Compressed(compressed.proof),
+            stdin,
+            public_values,
+            sp1_version,
+        };
+    }
+
+    // Shrunk, and wrapped in a STARK over the BN254 scalar field
+    let stage = Instant::now();
+    let shrunk = prover
+        .shrink(compressed, opts)
+        .expect("failed to shrink proof");
+    print_stage("shrink", stage, &shrunk);
+    let stage = Instant::now();
+    let wrapped = prover
+        .wrap_bn254(shrunk, opts)
+        .expect("failed to wrap proof");
+    print_stage("wrap", stage, &wrapped);
+
+    // Wrapped in a PLONK or Groth16 proof. The circuit artifacts are
+    // downloaded (once), or built for the wrapped proof in SP1 dev mode, before
+    // the stage is timed.
+    let artifacts = match (sp1_dev_mode(), mode) {
+        (false, _) => try_install_circuit_artifacts(),
+        (true, Mode::Plonk) => {
+            try_build_plonk_bn254_artifacts_dev(prover.wrap_vk(), &wrapped.proof)
+        }
+        (true, _) => try_build_groth16_bn254_artifacts_dev(prover.wrap_vk(), &wrapped.proof),
+    };
+    let stage = Instant::now();
+    let proof = match mode {
+        Mode::Plonk => SP1Proof::Plonk(prover.wrap_plonk_bn254(wrapped, &artifacts)),
+        Mode::Groth16 => SP1Proof::Groth16(prover.wrap_groth16_bn254(wrapped, &artifacts)),
+        Mode::Core | Mode::Compressed => unreachable!(),
+    };
+    print_stage(mode.extension(), stage, &proof);
+    SP1ProofWithPublicValues {
+        proof,
+        stdin,
+        public_values,
+        sp1_version,
+    }
+}
+
+/// Report the time taken by a proving stage and the size of its proof.
+fn print_stage<T: serde::Serialize>(name: &str, start: Instant, proof: &T) {
+    println!("Stage {name} time: {:.2}s", start.elapsed().as_secs_f64());
+    println!(
+        "Stage {name} proof size: {} bytes",
+        bincode::serialize(proof).unwrap().len()
+    );
+}
@@ -0,0 +1,47 @@
+#!/usr/bin/env bash
+#
+# Run the benchmark for each combination of batch size, shard size and shard
+# batch size, saving the output of each run to ../results/ and printing a
+# summary of the cycles, peak heap usage and proof generation time.
+#
+# Usage (from the script directory, after `cargo build --release`):
+#   ./sweep.sh [batch sizes] [log2 shard sizes] [shard batch sizes]
+#
+# e.g. ./sweep.sh "1 2 4 8" "20 21 22" "8 16". Set MODE to choose the proof
+# kind (default plonk).
+
+set -euo pipefail
+
+BATCH_SIZES=${1:-"1 2 4 8"}
+SHARD_SIZE_LOGS=${2:-"20 21 22"}
+SHARD_BATCH_SIZES=${3:-"8 16"}
+MODE=${MODE:-plonk}
+
+mkdir -p ../results
+
+for n in ${BATCH_SIZES}; do
+    for log_shard in ${SHARD_SIZE_LOGS}; do
+        for shard_batch in ${SHARD_BATCH_SIZES}; do
+            log=../results/sp1_batchsize_${n}_shard_${log_shard}_batch_${shard_batch}.log
+            RUST_LOG=info ./target/release/prove \
+                --n "${n}" --mode "${MODE}" \
+                --shard-size "$((1 << log_shard))" \
+                --shard-batch-size "${shard_batch}" \
+                2>&1 | tee "${log}"
+        done
+    done
+done
+
+echo
+echo "batch_size log2_shard_size shard_batch_size total_cycles peak_heap_bytes proof_generation_time"
+for n in ${BATCH_SIZES}; do
+    for log_shard in ${SHARD_SIZE_LOGS}; do
+        for shard_batch in ${SHARD_BATCH_SIZES}; do
+            log=../results/sp1_batchsize_${n}_shard_${log_shard}_batch_${shard_batch}.log
+            cycles=$(sed -n 's/^Total cycles: //p' "${log}")
+            heap=$(sed -n 's/^Peak heap: \([0-9]*\) bytes$/\1/p' "${log}")
+            time=$(sed -n 's/^Proof generation time: //p' "${log}")
+            echo "${n} ${log_shard} ${shard_batch} ${cycles} ${heap} ${time}"
+        done
+    done
+done
@@ -0,0 +1,60 @@
+//! Check the program cycle counts against the baseline in
+//! `tests/cycles_baseline.json`, failing if any phase has become more
+//! expensive or has no baseline. Run with `UPDATE_CYCLE_BASELINE=1` to record
+//! a new baseline.
+use groth16::batch::Batch;
+use groth16::cycle_baseline::{check_cycle_baseline, Baseline, Cycles, BATCH_SIZES};
+use groth16::sample::sample_batch;
+use sp1_groth16_aggregation_script::{execute, write_program_input};
+use sp1_sdk::ProverClient;
+
+const BASELINE_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cycles_baseline.json");
+
+/// Memory used by a run of the program
+struct Memory {
+    /// Peak heap usage reported by the program, in bytes
+    peak_heap: u64,
+    /// Number of distinct memory addresses touched
+    touched_addresses: u64,
+}
+
+/// Execute the program, returning the total cycles and the cycles of each
+/// phase marked with the cycle tracker, and the memory it used.
+fn measure(client: &ProverClient, batch: &Batch, streaming: bool) -> (Cycles, Memory) {
+    let (report, peak_heap) = execute(client, write_program_input(batch, streaming)).unwrap();
+    let mut cycles: Cycles = report
+        .cycle_tracker
+        .iter()
+        .map(|(phase, count)| (phase.clone(), *count))
+        .collect();
+    cycles.insert("total".to_string(), report.total_instruction_count());
+    let memory = Memory {
+        peak_heap,
+        touched_addresses: report.touched_memory_addresses,
+    };
+    (cycles, memory)
+}
+
+#[test]
+fn test_cycle_counts() {
+    // Runs are labelled by batch size, with streaming runs marked as such.
+    let client = ProverClient::mock();
+    let mut measured = Baseline::new();
+    for n in BATCH_SIZES {
+        let batch = sample_batch(n);
+        let (cycles, memory) = measure(&client, &batch, false);
+        let (streaming_cycles, streaming_memory) = measure(&client, &batch, true);
+        println!(
+            "batch size {n}: peak heap {} bytes, {} streaming; \
+             {} memory addresses, {} streaming",
+            memory.peak_heap,
+            streaming_memory.peak_heap,
+            memory.touched_addresses,
+            streaming_memory.touched_addresses
+        );
+        measured.insert(format!("{n}"), cycles);
+        measured.insert(format!("{n} streaming"), streaming_cycles);
+    }
+
+    check_cycle_baseline(BASELINE_FILE, &measured);
+}
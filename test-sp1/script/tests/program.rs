@@ -0,0 +1,128 @@
+//! Run the program with the mock prover, which executes the program but skips
+//! proof generation.
+use ark_bn254::Fr;
+use ark_ff::One;
+use groth16::batch::Batch;
+use groth16::error::VerifyError;
+use groth16::has_primitive_repr::HasPrimitiveRepr;
+use groth16::input_header::{InputHeader, INPUT_VERSION};
+use groth16::invalid_cases::{load_invalid_cases, INVALID_CASES_FILE};
+use groth16::sample::{sample_batch, SAMPLE_APPLICATION_PROOFS_FILE, SAMPLE_VK_TABLE_FILE};
+use sp1_core_executor::ExecutionError;
+use sp1_groth16_aggregation_script::{write_program_input, FD_ERROR, G16_AGGREGATION_ELF};
+use sp1_sdk::{ProverClient, SP1Stdin};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_program_valid_proof() {
+    let batch = sample_batch(2);
+
+    let client = ProverClient::mock();
+    let (pk, program_vk) = client.setup(G16_AGGREGATION_ELF);
+    for streaming in [false, true] {
+        let stdin = write_program_input(&batch, streaming);
+        let mut agg_proof = client.prove(&pk, stdin).run().unwrap();
+        client.verify(&agg_proof, &program_vk).unwrap();
+        let batch_size: u32 = agg_proof.public_values.read();
+        assert_eq!(batch_size, 2);
+    }
+}
+
+#[test]
+fn test_program_invalid_proof() {
+    // Failure case
+    let mut batch = sample_batch(1);
+    batch.entries[0].inputs[0] = Fr::one();
+    let client = ProverClient::mock();
+    let (pk, _) = client.setup(G16_AGGREGATION_ELF);
+    let stdin = write_program_input(&batch, false);
+    assert_panicked(&client.prove(&pk, stdin).run().unwrap_err());
+    let err = execute_rejected(&client, write_program_input(&batch, false));
+    assert_eq!(err, format!("proof 0: {}", VerifyError::PairingMismatch));
+}
+
+#[test]
+fn test_program_invalid_cases() {
+    // Each case is rejected by the program with the expected reason
+    let client = ProverClient::mock();
+    for case in load_invalid_cases(INVALID_CASES_FILE) {
+        let (header, batch_repr) = case.guest_input().unwrap();
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&header);
+        stdin.write(&batch_repr);
+        let err = execute_rejected(&client, stdin);
+        let reason = case.expected.to_string();
+        assert!(err.contains(&reason), "{}: {err}", case.name);
+    }
+}
+
+/// Execute the program, which must reject its input, and return the reason
+/// it gives.
+fn execute_rejected(client: &ProverClient, stdin: SP1Stdin) -> String {
+    let reason = Arc::new(Mutex::new(String::new()));
+    let hook_reason = reason.clone();
+    let result = client
+        .execute(G16_AGGREGATION_ELF, stdin)
+        .with_hook(FD_ERROR, move |_, buf| {
+            *hook_reason.lock().unwrap() = String::from_utf8_lossy(buf).into_owned();
+            vec![]
+        })
+        .run();
+    assert_panicked(&result.expect_err("input accepted"));
+    let reason = reason.lock().unwrap().clone();
+    reason
+}
+
+/// Assert that the program failed by panicking, which halts it with exit
+/// code 1.
+fn assert_panicked(err: &anyhow::Error) {
+    assert!(
+        matches!(
+            err.downcast_ref(),
+            Some(ExecutionError::HaltWithNonZeroExitCode(1))
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_program_vk_index_out_of_range() {
+    // The second proof refers to a verification key which is not in the batch
+    let mut batch = sample_batch(2);
+    batch.entries[1].vk_index = 1;
+    let client = ProverClient::mock();
+    for streaming in [false, true] {
+        let err = execute_rejected(&client, write_program_input(&batch, streaming));
+        assert!(err.contains("proof 1: verification key index"), "{err}");
+    }
+}
+
+#[test]
+fn test_program_application_proofs() {
+    let batch = Batch::load(SAMPLE_APPLICATION_PROOFS_FILE, SAMPLE_VK_TABLE_FILE).unwrap();
+
+    let client = ProverClient::mock();
+    let (pk, program_vk) = client.setup(G16_AGGREGATION_ELF);
+    let stdin = write_program_input(&batch, false);
+    let mut agg_proof = client.prove(&pk, stdin).run().unwrap();
+    client.verify(&agg_proof, &program_vk).unwrap();
+    let batch_size: u32 = agg_proof.public_values.read();
+    assert_eq!(batch_size, batch.len() as u32);
+}
+
+#[test]
+fn test_program_input_version_mismatch() {
+    let batch = sample_batch(1);
+
+    // Input written for a different version of the program
+    let header = InputHeader {
+        version: INPUT_VERSION + 1,
+        ..InputHeader::new(&batch, false)
+    };
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&header);
+    stdin.write(&batch.to_repr());
+    let client = ProverClient::mock();
+    let err = execute_rejected(&client, stdin);
+    assert!(err.contains("invalid program input"), "{err}");
+}